@@ -1,29 +1,44 @@
-use serialport::{SerialPort, new};
-use laserport::dmx;
-use std::time::Duration;
-use std::io::Write;
+use laserport::dmx::{DmxController, DmxState, DMX_FRAME_SIZE};
+use laserport::playback::{Chase, Step};
+use laserport::transport::discovery::{self, DiscoveredPort};
+use laserport::transport::SerialAdapter;
+use std::time::{Duration, Instant};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-
-
-    // List all DMX-compatible ports using dmx::available_dmx_ports
-    println!("Scanning available serial ports for DMX compatibility:");
-    let dmx_ports = dmx::scan_dmx_ports();
-    let baud_rate = 250_000; // DMX standard baud rate
+    // Go by USB descriptors alone; `--probe` also asks likely ports to identify themselves
+    println!("Looking for DMX adapters:");
+    let ports = if std::env::args().any(|arg| arg == "--probe") {
+        discovery::discover_and_probe(discovery::DEFAULT_PROBE_TIMEOUT)?
+    } else {
+        discovery::discover_ports()?
+    };
+    let dmx_ports: Vec<_> = ports
+        .into_iter()
+        .filter_map(|port| port.likely_adapter().map(|adapter| (port, adapter)))
+        .collect();
     if dmx_ports.is_empty() {
-        println!("No DMX-compatible ports found.");
+        println!("No DMX adapters found.");
         return Ok(());
     }
 
+    let describe = |(port, adapter): &(DiscoveredPort, SerialAdapter)| {
+        format!(
+            "{} ({:?}, {} {})",
+            port.port_name,
+            adapter,
+            port.product.as_deref().unwrap_or("unknown product"),
+            port.serial_number.as_deref().unwrap_or("")
+        )
+    };
 
     // Select DMX port: auto if one, prompt if multiple
-    let port_name = if dmx_ports.len() == 1 {
-        println!("\nOnly one DMX-compatible port found: {}", dmx_ports[0]);
+    let (port, adapter) = if dmx_ports.len() == 1 {
+        println!("\nOnly one DMX adapter found: {}", describe(&dmx_ports[0]));
         &dmx_ports[0]
     } else {
-        println!("\nMultiple DMX-compatible ports found:");
+        println!("\nMultiple DMX adapters found:");
         for (i, p) in dmx_ports.iter().enumerate() {
-            println!("  [{}] {}", i + 1, p);
+            println!("  [{}] {}", i + 1, describe(p));
         }
         use std::io::{self, Write};
         loop {
@@ -31,76 +46,68 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             io::stdout().flush().unwrap();
             let mut input = String::new();
             io::stdin().read_line(&mut input)?;
-            if let Ok(idx) = input.trim().parse::<usize>() {
-                if idx >= 1 && idx <= dmx_ports.len() {
-                    break &dmx_ports[idx - 1];
-                }
+            if let Ok(idx) = input.trim().parse::<usize>()
+                && (1..=dmx_ports.len()).contains(&idx)
+            {
+                break &dmx_ports[idx - 1];
             }
             println!("Invalid selection. Please enter a valid number.");
         }
     };
-    println!("\nUsing DMX port: {}", port_name);
-    let mut port = serialport::new(port_name, baud_rate)
-        .timeout(Duration::from_millis(100))
-        .stop_bits(serialport::StopBits::Two)
-        .open()?;
+    println!("\nUsing DMX port: {}", port.port_name);
+    let controller = DmxController::with_adapter(&port.port_name, *adapter, 1)?;
 
     println!("Connected to DMX adapter. Testing CH1 (Shutter) features...");
 
-
-    // DMX universe (512 bytes, initialized to 0)
-    let mut dmx_universe = vec![0u8; 512];
-    // Set DMX address to 1
-    dmx_universe[1] = 0;
-    // Send initial frame with break and start code
-    port.set_break()?;
-    std::thread::sleep(Duration::from_micros(100));
-    port.clear_break()?;
-    let mut frame = vec![0x00];
-    frame.extend_from_slice(&dmx_universe);
-    port.write_all(&frame)?;
+    // DMX universe (512 channels, initialized to 0), laser at address 1, refreshed continuously
+    let refresh = controller.start_refresh(DmxState::new(DMX_FRAME_SIZE), 40.0)?;
+    let dmx = refresh.handle();
     println!("Set DMX address to 1");
 
-    // Test CH1 and CH4: Toggle shutter and activate built-in visual
-    for _ in 0..5 {
-        // Turn laser off (CH1 = 0)
-        dmx_universe[0] = 0;
-        dmx_universe[3] = 0;
-        port.set_break()?;
-        std::thread::sleep(Duration::from_micros(100));
-        port.clear_break()?;
-        let mut frame = vec![0x00];
-        frame.extend_from_slice(&dmx_universe);
-        port.write_all(&frame)?;
-        println!("CH1 set to 0 (Off), CH4 set to 0 (No Pattern)");
-        std::thread::sleep(Duration::from_secs(2));
+    // Test CH1 and CH4: toggle shutter and the built-in Christmas graphic (CH4 = 100), five times over
+    let off = Step::new(Duration::from_secs(2)).with_channel(1, 0).with_channel(4, 0);
+    let on = Step::new(Duration::from_secs(3)).with_channel(1, 255).with_channel(4, 100);
+    let chase = Chase::new(vec![off, on])?.with_loops(5);
+    dmx.add_source(Box::new(chase.clone()));
+    chase.start();
+    // The start is picked up by the next rendered frame; until then the chase isn't running yet
+    let waiting = Instant::now();
+    while chase.current_step().is_none() {
+        if waiting.elapsed() > Duration::from_secs(2) {
+            // Nothing is rendering frames any more: report why rather than wait for good
+            let error = refresh.last_error().unwrap_or_else(|| "no frame rendered".to_string());
+            refresh.stop()?;
+            return Err(format!("Chase never started: {}", error).into());
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
 
-        // Turn laser on with built-in Christmas graphic (CH4 = 100)
-        dmx_universe[0] = 255;
-        dmx_universe[3] = 100;
-        port.set_break()?;
-        std::thread::sleep(Duration::from_micros(100));
-        port.clear_break()?;
-        let mut frame = vec![0x00];
-        frame.extend_from_slice(&dmx_universe);
-        if let Err(e) = port.write_all(&frame) {
-            println!("Write error: {}. Check connection or address.", e);
-        } else {
-            println!("CH1 set to 255 (On), CH4 set to 100 (Christmas Graphic)");
+    let mut shown = None;
+    loop {
+        std::thread::sleep(Duration::from_millis(100));
+        if !chase.is_running() {
+            break;
+        }
+        let step = chase.current_step();
+        if step != shown {
+            match (step, refresh.last_error()) {
+                (_, Some(e)) => println!("Write error: {}. Check connection or address.", e),
+                (Some(0), None) => println!("CH1 set to 0 (Off), CH4 set to 0 (No Pattern)"),
+                _ => println!("CH1 set to 255 (On), CH4 set to 100 (Christmas Graphic)"),
+            }
+            shown = step;
         }
-        std::thread::sleep(Duration::from_secs(3));
     }
+    dmx.clear_sources();
 
     // Clean up: Turn off laser
-    dmx_universe[0] = 0;
-    dmx_universe[3] = 0;
-    port.set_break()?;
-    std::thread::sleep(Duration::from_micros(100));
-    port.clear_break()?;
-    let mut frame = vec![0x00];
-    frame.extend_from_slice(&dmx_universe);
-    port.write_all(&frame)?;
+    dmx.update(|universe| {
+        universe.set_channel(1, 0);
+        universe.set_channel(4, 0);
+    });
+    std::thread::sleep(Duration::from_millis(100));
+    refresh.stop()?.close()?;
     println!("Test complete. Laser turned off.");
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,25 +1,12 @@
-use serialport::{self, DataBits, FlowControl, Parity, StopBits};
-use std::io::Write;
-use std::time::Duration;
+use laserport::dmx::{DmxController, DmxState};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let port_name = "COM4"; // Adjust as needed
-    let mut port = serialport::new(port_name, 250_000)
-        .data_bits(DataBits::Eight)
-        .flow_control(FlowControl::None)
-        .parity(Parity::None)
-        .stop_bits(StopBits::Two)
-        .timeout(Duration::from_millis(10))
-        .open()?;
+    let port_name = std::env::args().nth(1).unwrap_or_else(|| "COM4".to_string()); // Adjust as needed
+    let mut controller = DmxController::connect(&port_name, 1)?;
 
-    // Corrected: Remove argument from set_break
-    port.set_break(); // Emulate break
-    std::thread::sleep(Duration::from_micros(100));
-    port.clear_break(); // Clear break (no arg needed)
+    let mut state = DmxState::new(16);
+    state.channels.copy_from_slice(&[255, 70, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    controller.send(&state)?;
 
-    let mut frame = vec![0x00]; // Start code
-    frame.extend_from_slice(&[255, 70, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-    frame.resize(512, 0);
-    port.write_all(&frame)?;
-
-    Ok(())}
\ No newline at end of file
+    Ok(())
+}
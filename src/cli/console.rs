@@ -0,0 +1,262 @@
+//! `laserport console`: full-screen faders for a ZQ03268.
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use laserport::dmx::{DmxController, DmxHandle, DmxState, DMX_FRAME_SIZE};
+use laserport::dmxcharts::ZQ03268::{self, LaserState, CHANNEL_COUNT};
+use laserport::fixture::ChannelDefinition;
+
+const PAGE_STEP: u8 = 10;
+
+/// Fader values, selection and the dropdown; everything the screen is drawn from.
+struct Console {
+    handle: DmxHandle,
+    address: usize,
+    /// The fixture's channels in DMX order, for labels and dropdown entries.
+    channels: Vec<ChannelDefinition>,
+    values: [u8; CHANNEL_COUNT],
+    selected: usize,
+    /// Highlighted capability while the selected channel's dropdown is open.
+    dropdown: Option<usize>,
+    /// While set the universe is held at zero; faders keep their values for when it's released.
+    blackout: bool,
+}
+
+impl Console {
+    fn new(handle: DmxHandle, address: usize) -> Result<Self, Box<dyn Error>> {
+        let definition = ZQ03268::definition();
+        let mode = definition.modes.first().ok_or("ZQ03268 definition has no modes")?;
+        let channels = mode
+            .channels
+            .iter()
+            .map(|name| definition.channel(name).cloned().ok_or_else(|| format!("undefined channel '{}'", name)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if channels.len() != CHANNEL_COUNT {
+            return Err(format!("expected {} channels in ZQ03268 mode, found {}", CHANNEL_COUNT, channels.len()).into());
+        }
+        let mut values = [0u8; CHANNEL_COUNT];
+        for (index, value) in values.iter_mut().enumerate() {
+            *value = handle.get_channel(address + index).unwrap_or(0);
+        }
+        Ok(Console { handle, address, channels, values, selected: 0, dropdown: None, blackout: false })
+    }
+
+    fn set(&mut self, index: usize, value: u8) {
+        self.values[index] = value;
+        if !self.blackout {
+            self.handle.set_channel(self.address + index, value);
+        }
+    }
+
+    fn nudge(&mut self, delta: i16) {
+        let value = (self.values[self.selected] as i16 + delta).clamp(0, 255) as u8;
+        self.set(self.selected, value);
+    }
+
+    fn toggle_blackout(&mut self) {
+        self.blackout = !self.blackout;
+        if self.blackout {
+            self.handle.update(|state| state.channels.fill(0));
+        } else {
+            for (index, &value) in self.values.iter().enumerate() {
+                self.handle.set_channel(self.address + index, value);
+            }
+        }
+    }
+
+    /// Handles one key press. Returns false when the user asked to quit.
+    fn key(&mut self, key: KeyEvent) -> bool {
+        if let Some(highlighted) = self.dropdown {
+            let capabilities = &self.channels[self.selected].capabilities;
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.dropdown = Some(highlighted.saturating_sub(1)),
+                KeyCode::Down | KeyCode::Char('j') => self.dropdown = Some((highlighted + 1).min(capabilities.len() - 1)),
+                KeyCode::Enter => {
+                    let value = capabilities[highlighted].center();
+                    self.set(self.selected, value);
+                    self.dropdown = None;
+                }
+                KeyCode::Esc => self.dropdown = None,
+                KeyCode::Char(' ') | KeyCode::Char('b') => self.toggle_blackout(),
+                _ => {}
+            }
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::BackTab => {
+                self.selected = (self.selected + CHANNEL_COUNT - 1) % CHANNEL_COUNT
+            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Tab => self.selected = (self.selected + 1) % CHANNEL_COUNT,
+            KeyCode::Up | KeyCode::Char('k') => self.nudge(1),
+            KeyCode::Down | KeyCode::Char('j') => self.nudge(-1),
+            KeyCode::PageUp => self.nudge(PAGE_STEP as i16),
+            KeyCode::PageDown => self.nudge(-(PAGE_STEP as i16)),
+            KeyCode::Home => self.set(self.selected, 255),
+            KeyCode::End => self.set(self.selected, 0),
+            KeyCode::Enter => {
+                let channel = &self.channels[self.selected];
+                if channel.capabilities.len() > 1 {
+                    let current = self.values[self.selected];
+                    self.dropdown = Some(channel.capabilities.iter().position(|cap| cap.contains(current)).unwrap_or(0));
+                }
+            }
+            KeyCode::Char(' ') | KeyCode::Char('b') => self.toggle_blackout(),
+            _ => {}
+        }
+        true
+    }
+
+    fn capability_name(&self, index: usize) -> &str {
+        self.channels[index].capability_at(self.values[index]).map(|cap| cap.name.as_str()).unwrap_or("")
+    }
+
+    fn draw(&self, frame: &mut Frame, rate: f32, fps: f32, error: Option<&str>) {
+        let [title, faders, blackout, status] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(8), Constraint::Length(3), Constraint::Length(1)])
+                .areas(frame.area());
+
+        let fps_style = if fps < rate * 0.9 { Style::new().fg(Color::Yellow) } else { Style::new().fg(Color::Green) };
+        frame.render_widget(
+            Paragraph::new(format!("ZQ03268 at address {}   {:.1} / {} fps", self.address, fps, rate))
+                .style(fps_style.add_modifier(Modifier::BOLD)),
+            title,
+        );
+
+        let columns = Layout::horizontal([Constraint::Ratio(1, CHANNEL_COUNT as u32); CHANNEL_COUNT]).split(faders);
+        for (index, &area) in columns.iter().enumerate() {
+            self.draw_fader(frame, index, area);
+        }
+
+        let (label, style) = if self.blackout {
+            ("BLACKOUT  (space to release)", Style::new().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD))
+        } else {
+            ("blackout  [space]", Style::new().fg(Color::Red))
+        };
+        frame.render_widget(
+            Paragraph::new(label).alignment(Alignment::Center).style(style).block(Block::bordered().border_style(style)),
+            blackout,
+        );
+
+        let status_line = match error {
+            Some(e) => Paragraph::new(format!("output error: {}", e)).style(Style::new().fg(Color::Red)),
+            None => Paragraph::new(format!(
+                "CH{} {} = {} {}   ←→ select  ↑↓ PgUp PgDn Home End adjust  Enter choose  q quit",
+                self.selected + 1,
+                self.channels[self.selected].name,
+                self.values[self.selected],
+                self.capability_name(self.selected),
+            )),
+        };
+        frame.render_widget(status_line, status);
+
+        if let Some(highlighted) = self.dropdown {
+            self.draw_dropdown(frame, highlighted, columns[self.selected], faders);
+        }
+    }
+
+    fn draw_fader(&self, frame: &mut Frame, index: usize, area: Rect) {
+        let selected = index == self.selected;
+        let border = if selected { Style::new().fg(Color::Yellow) } else { Style::new().fg(Color::DarkGray) };
+        let block = Block::bordered().title(format!("{}", index + 1)).border_style(border);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        if inner.height < 3 || inner.width == 0 {
+            return;
+        }
+
+        // Bar, then value, channel name and what the value means underneath
+        let [bar, value, name, meaning] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1), Constraint::Length(1), Constraint::Length(1)])
+                .areas(inner);
+        let filled = (self.values[index] as u32 * bar.height as u32).div_ceil(255) as u16;
+        let fill = match (self.blackout, selected) {
+            (true, _) => Style::new().fg(Color::DarkGray),
+            (false, true) => Style::new().fg(Color::Yellow),
+            (false, false) => Style::new().fg(Color::Cyan),
+        };
+        let buffer = frame.buffer_mut();
+        for row in 0..bar.height {
+            let y = bar.bottom() - 1 - row;
+            let (symbol, style) = if row < filled { ("█", fill) } else { ("·", Style::new().fg(Color::DarkGray)) };
+            buffer.set_string(bar.x, y, symbol.repeat(bar.width as usize), style);
+        }
+
+        frame.render_widget(Paragraph::new(self.values[index].to_string()).alignment(Alignment::Center), value);
+        frame.render_widget(Paragraph::new(self.channels[index].name.as_str()).alignment(Alignment::Center), name);
+        frame.render_widget(
+            Paragraph::new(self.capability_name(index)).alignment(Alignment::Center).style(Style::new().fg(Color::Gray)),
+            meaning,
+        );
+    }
+
+    fn draw_dropdown(&self, frame: &mut Frame, highlighted: usize, column: Rect, bounds: Rect) {
+        let channel = &self.channels[self.selected];
+        let items: Vec<ListItem> = channel
+            .capabilities
+            .iter()
+            .map(|cap| ListItem::new(format!("{:>3}-{:<3} {}", cap.min, cap.max, cap.name)))
+            .collect();
+        let longest = channel.capabilities.iter().map(|cap| cap.name.chars().count()).max().unwrap_or(0);
+        let width = (longest as u16 + 12).min(bounds.width);
+        let height = (items.len() as u16 + 2).min(bounds.height);
+        let x = column.x.min(bounds.right().saturating_sub(width));
+        let area = Rect::new(x, bounds.y, width, height);
+
+        let list = List::new(items)
+            .block(Block::bordered().title(format!("CH{} {}", self.selected + 1, channel.name)))
+            .highlight_style(Style::new().fg(Color::Black).bg(Color::Yellow));
+        let mut state = ListState::default().with_selected(Some(highlighted));
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+}
+
+/// Runs the console until `q`, refreshing the universe at `rate` Hz.
+pub fn run(controller: DmxController, address: usize, rate: f32) -> Result<(), Box<dyn Error>> {
+    let mut universe = DmxState::new(DMX_FRAME_SIZE);
+    super::place(&mut universe, address, &LaserState::new().to_channels())?;
+    let refresh = controller.start_refresh(universe, rate)?;
+    let mut console = Console::new(refresh.handle(), address)?;
+
+    let mut terminal = ratatui::try_init()?;
+    let mut fps = 0.0;
+    let mut last_frames = refresh.frames_sent();
+    let mut last_sample = Instant::now();
+    let result = loop {
+        if last_sample.elapsed() >= Duration::from_secs(1) {
+            let frames = refresh.frames_sent();
+            fps = (frames - last_frames) as f32 / last_sample.elapsed().as_secs_f32();
+            last_frames = frames;
+            last_sample = Instant::now();
+        }
+        let error = refresh.last_error();
+        if let Err(e) = terminal.draw(|frame| console.draw(frame, rate, fps, error.as_deref())) {
+            break Err(e);
+        }
+        match event::poll(Duration::from_millis(100)) {
+            Ok(false) => continue,
+            Ok(true) => {}
+            Err(e) => break Err(e),
+        }
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                if !console.key(key) {
+                    break Ok(());
+                }
+            }
+            Ok(_) => {}
+            Err(e) => break Err(e),
+        }
+    };
+    ratatui::restore();
+    result?;
+    refresh.stop()?.close()
+}
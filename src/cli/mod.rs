@@ -0,0 +1,361 @@
+//! The `laserport` command-line tool.
+
+mod console;
+mod repl;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::error::Error;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use laserport::dmx::{DmxController, DmxState, DMX_FRAME_SIZE};
+use laserport::dmxcharts::ZQ03268::{ColorMode, GraphicsGroup, LaserState, MainSwitch};
+use laserport::transport::discovery::{self, DiscoveredPort, ProbeResult};
+use laserport::transport::{AdapterAliases, ReconnectingTransport, SerialAdapter};
+
+#[derive(Parser)]
+#[command(name = "laserport", version, about = "Drive DMX fixtures from the command line")]
+pub struct Cli {
+    /// Adapter to use: a port name (COM4, /dev/ttyUSB0), serial:<USB serial> or alias:<name>.
+    /// Defaults to the only DMX adapter plugged in.
+    #[arg(long, short, global = true, env = "LASERPORT_PORT")]
+    pub port: Option<String>,
+
+    /// Adapter type, if it can't be told from the USB descriptors.
+    #[arg(long, global = true, value_enum)]
+    pub adapter: Option<AdapterKind>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum AdapterKind {
+    OpenDmx,
+    EnttecPro,
+}
+
+impl From<AdapterKind> for SerialAdapter {
+    fn from(kind: AdapterKind) -> Self {
+        match kind {
+            AdapterKind::OpenDmx => SerialAdapter::OpenDmx,
+            AdapterKind::EnttecPro => SerialAdapter::EnttecPro,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List serial ports and what is plugged into them.
+    Ports {
+        /// Also ask likely DMX adapters to identify themselves. This writes to those ports.
+        #[arg(long)]
+        probe: bool,
+    },
+    /// Full-screen fader console for a ZQ03268.
+    Console {
+        /// DMX start address of the laser.
+        #[arg(long, short, default_value_t = 1)]
+        address: usize,
+        /// Frames per second.
+        #[arg(long, default_value_t = 40.0)]
+        rate: f32,
+    },
+    /// Interactive shell for a ZQ03268: `ch7 200`, `color seg8`, `fade ch8 0..255 3s`.
+    Shell {
+        /// DMX start address of the laser.
+        #[arg(long, short, default_value_t = 1)]
+        address: usize,
+        /// Frames per second.
+        #[arg(long, default_value_t = 40.0)]
+        rate: f32,
+    },
+    /// Keep sending a universe until stopped, e.g. `watch laser zq03268 --color rainbow`.
+    Watch {
+        /// Frames per second.
+        #[arg(long, default_value_t = 40.0)]
+        rate: f32,
+        /// Stop after this many seconds instead of running until interrupted.
+        #[arg(long, value_parser = parse_seconds)]
+        seconds: Option<Duration>,
+        #[command(subcommand)]
+        output: Output,
+    },
+    #[command(flatten)]
+    Output(Output),
+}
+
+/// Commands that build one universe. On their own they send a single frame;
+/// fixtures that black out without a signal need `watch`.
+#[derive(Subcommand)]
+pub enum Output {
+    /// Set channels: `set 1=255 5=12 20-24=0`.
+    Set {
+        #[arg(required = true, value_name = "CH=VALUE")]
+        assignments: Vec<String>,
+    },
+    /// Send all 512 channels at zero.
+    Blackout,
+    /// Send values on consecutive channels starting at an address: `send --address 17 255 70`.
+    Send {
+        #[arg(long, short, default_value_t = 1)]
+        address: usize,
+        #[arg(required = true)]
+        values: Vec<u8>,
+    },
+    /// Drive a modelled fixture by its functions.
+    Laser {
+        #[command(subcommand)]
+        model: LaserModel,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LaserModel {
+    /// U'King ZQ03268 in its 16-channel mode.
+    Zq03268(Zq03268Args),
+}
+
+#[derive(Args)]
+pub struct Zq03268Args {
+    /// DMX start address of the laser.
+    #[arg(long, short, default_value_t = 1)]
+    pub address: usize,
+    /// Switch the laser output off (CH1).
+    #[arg(long)]
+    pub off: bool,
+    /// Colour mode (white, red, ..., rainbow, seg8, gradient) or #rrggbb for the nearest fixed colour.
+    #[arg(long)]
+    pub color: Option<String>,
+    /// Pattern group (static1-static5, anim1-anim5).
+    #[arg(long)]
+    pub group: Option<String>,
+    /// Pattern within the group (CH5).
+    #[arg(long)]
+    pub pattern: Option<u8>,
+    /// Effect speed (CH7).
+    #[arg(long)]
+    pub speed: Option<u8>,
+    /// Pattern size (CH8).
+    #[arg(long)]
+    pub size: Option<u8>,
+}
+
+pub fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    match cli.command {
+        Command::Ports { probe } => list_ports(probe),
+        Command::Console { address, rate } => console::run(open_controller(&cli)?, address, rate),
+        Command::Shell { address, rate } => repl::run(open_controller(&cli)?, address, rate),
+        Command::Output(ref output) => {
+            let universe = build_universe(output)?;
+            let mut controller = open_controller(&cli)?;
+            controller.send(&universe)?;
+            controller.close()
+        }
+        Command::Watch { rate, seconds, ref output } => {
+            let universe = build_universe(output)?;
+            watch(open_controller(&cli)?, universe, rate, seconds)
+        }
+    }
+}
+
+fn open_controller(cli: &Cli) -> Result<DmxController, Box<dyn Error>> {
+    let selector = match &cli.port {
+        Some(port) => port.clone(),
+        None => only_adapter()?,
+    };
+    let transport = ReconnectingTransport::serial(
+        selector.parse()?,
+        AdapterAliases::load_default()?,
+        cli.adapter.map(SerialAdapter::from),
+    );
+    DmxController::with_transport(Box::new(transport), 1)
+}
+
+fn only_adapter() -> Result<String, Box<dyn Error>> {
+    let ports: Vec<DiscoveredPort> =
+        discovery::discover_ports()?.into_iter().filter(|port| port.likely_adapter().is_some()).collect();
+    match ports.as_slice() {
+        [port] => Ok(match &port.serial_number {
+            Some(serial) => format!("serial:{}", serial),
+            None => port.port_name.clone(),
+        }),
+        [] => Err("no DMX adapter found; plug one in or pass --port".into()),
+        _ => {
+            let names: Vec<&str> = ports.iter().map(|port| port.port_name.as_str()).collect();
+            Err(format!("several DMX adapters found ({}); pick one with --port", names.join(", ")).into())
+        }
+    }
+}
+
+fn list_ports(probe: bool) -> Result<(), Box<dyn Error>> {
+    let ports = if probe {
+        discovery::discover_and_probe(discovery::DEFAULT_PROBE_TIMEOUT)?
+    } else {
+        discovery::discover_ports()?
+    };
+    if ports.is_empty() {
+        println!("No serial ports found.");
+    }
+    for port in &ports {
+        let usb = match (port.vid, port.pid) {
+            (Some(vid), Some(pid)) => format!("{:04x}:{:04x}", vid, pid),
+            _ => "-".to_string(),
+        };
+        let adapter = match port.likely_adapter() {
+            Some(SerialAdapter::OpenDmx) => "Open DMX",
+            Some(SerialAdapter::EnttecPro) => "Enttec Pro",
+            None => "-",
+        };
+        println!(
+            "{:<16} {:<10} {:<10} {:<12} {} {}",
+            port.port_name,
+            usb,
+            adapter,
+            port.serial_number.as_deref().unwrap_or("-"),
+            port.manufacturer.as_deref().unwrap_or(""),
+            port.product.as_deref().unwrap_or("")
+        );
+        match &port.probe {
+            Some(ProbeResult::EnttecPro { firmware_version, serial_number }) => println!(
+                "{:<16} widget serial {}, firmware {}.{}",
+                "",
+                serial_number,
+                firmware_version >> 8,
+                firmware_version & 0xFF
+            ),
+            Some(ProbeResult::NoReply) => println!("{:<16} no reply to probe", ""),
+            Some(ProbeResult::Unavailable(e)) => println!("{:<16} could not open: {}", "", e),
+            None => {}
+        }
+    }
+
+    let aliases = AdapterAliases::load_default()?;
+    let mut aliases = aliases.iter().peekable();
+    if aliases.peek().is_some() {
+        println!("\nAliases:");
+        for (alias, target) in aliases {
+            println!("  alias:{:<12} {}", alias, target);
+        }
+    }
+    Ok(())
+}
+
+fn build_universe(output: &Output) -> Result<DmxState, Box<dyn Error>> {
+    let mut universe = DmxState::new(DMX_FRAME_SIZE);
+    match output {
+        Output::Set { assignments } => {
+            for assignment in assignments {
+                let (channels, value) = parse_assignment(assignment)?;
+                for channel in channels {
+                    universe.set_channel(channel, value);
+                }
+            }
+        }
+        Output::Blackout => {}
+        Output::Send { address, values } => place(&mut universe, *address, values)?,
+        Output::Laser { model: LaserModel::Zq03268(args) } => {
+            place(&mut universe, args.address, &zq03268_state(args)?.to_channels())?
+        }
+    }
+    Ok(universe)
+}
+
+fn place(universe: &mut DmxState, address: usize, values: &[u8]) -> Result<(), Box<dyn Error>> {
+    if address == 0 || address + values.len() - 1 > DMX_FRAME_SIZE {
+        return Err(format!("{} channels at address {} don't fit in the universe", values.len(), address).into());
+    }
+    universe.channels[address - 1..address - 1 + values.len()].copy_from_slice(values);
+    Ok(())
+}
+
+/// Parses `--seconds`: a finite, non-negative number of seconds.
+fn parse_seconds(text: &str) -> Result<Duration, String> {
+    let seconds: f32 = text.parse().map_err(|_| format!("'{}' is not a number of seconds", text))?;
+    Duration::try_from_secs_f32(seconds).map_err(|_| format!("{} is not a usable number of seconds", text))
+}
+
+/// Parses `CH=VALUE` or `FIRST-LAST=VALUE` with 1-based channels.
+fn parse_assignment(text: &str) -> Result<(std::ops::RangeInclusive<usize>, u8), Box<dyn Error>> {
+    let (channels, value) = text.split_once('=').ok_or_else(|| format!("expected CH=VALUE, got '{}'", text))?;
+    let value: u8 = value.trim().parse().map_err(|_| format!("'{}' is not a DMX value (0-255)", value))?;
+    let channel = |s: &str| -> Result<usize, Box<dyn Error>> {
+        match s.trim().parse() {
+            Ok(channel) if (1..=DMX_FRAME_SIZE).contains(&channel) => Ok(channel),
+            _ => Err(format!("'{}' is not a DMX channel (1-512)", s).into()),
+        }
+    };
+    let range = match channels.split_once('-') {
+        Some((first, last)) => channel(first)?..=channel(last)?,
+        None => channel(channels)?..=channel(channels)?,
+    };
+    if range.is_empty() {
+        return Err(format!("channel range '{}' is backwards", channels).into());
+    }
+    Ok((range, value))
+}
+
+fn zq03268_state(args: &Zq03268Args) -> Result<LaserState, Box<dyn Error>> {
+    let mut state = LaserState::new();
+    state.ch1 = if args.off { MainSwitch::Off } else { MainSwitch::On };
+    if let Some(color) = &args.color {
+        state.ch2 = parse_color(color)?;
+    }
+    if let Some(group) = &args.group {
+        state.ch4 = GraphicsGroup::from_name(group).ok_or_else(|| {
+            format!("unknown pattern group '{}'; try one of {}", group, GraphicsGroup::NAMES.join(", "))
+        })?;
+    }
+    if let Some(pattern) = args.pattern {
+        state.ch5 = pattern;
+    }
+    if let Some(speed) = args.speed {
+        state.ch7 = speed;
+    }
+    if let Some(size) = args.size {
+        state.ch8 = size;
+    }
+    Ok(state)
+}
+
+fn parse_color(text: &str) -> Result<ColorMode, Box<dyn Error>> {
+    if let Some(hex) = text.strip_prefix('#')
+        && hex.len() == 6
+        && let Ok(rgb) = u32::from_str_radix(hex, 16)
+    {
+        return Ok(ColorMode::nearest_fixed(((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)));
+    }
+    ColorMode::from_name(text).ok_or_else(|| {
+        format!("unknown colour '{}'; try #rrggbb or one of {}", text, ColorMode::NAMES.join(", ")).into()
+    })
+}
+
+fn watch(controller: DmxController, universe: DmxState, rate: f32, seconds: Option<Duration>) -> Result<(), Box<dyn Error>> {
+    let refresh = controller.start_refresh(universe, rate)?;
+    let started = Instant::now();
+    // A deadline past what the clock can hold is as good as none
+    let deadline = seconds.and_then(|seconds| started.checked_add(seconds));
+    let mut last_frames = 0;
+
+    loop {
+        let now = Instant::now();
+        let wait = match deadline {
+            Some(deadline) if deadline <= now => break,
+            Some(deadline) => (deadline - now).min(Duration::from_secs(1)),
+            None => Duration::from_secs(1),
+        };
+        thread::sleep(wait);
+
+        let frames = refresh.frames_sent();
+        let fps = (frames - last_frames) as f32 / wait.as_secs_f32();
+        last_frames = frames;
+        match refresh.last_error() {
+            Some(e) => print!("\r{} frames, {:.1} fps, last error: {}   ", frames, fps, e),
+            None => print!("\r{} frames, {:.1} fps   ", frames, fps),
+        }
+        io::stdout().flush()?;
+    }
+    println!();
+    refresh.stop()?.close()
+}
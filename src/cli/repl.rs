@@ -0,0 +1,312 @@
+//! `laserport shell`: poke ZQ03268 channels live while the refresh loop runs.
+
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use laserport::dmx::{DmxController, DmxHandle, DmxState, DMX_FRAME_SIZE};
+use laserport::dmxcharts::ZQ03268::{ColorMode, GraphicsGroup, LaserState, MainSwitch, CHANNEL_COUNT, CHANNEL_NAMES};
+use laserport::transport::AdapterAliases;
+
+/// Short names for CH1..CH16, usable wherever `chN` is.
+const CHANNEL_KEYWORDS: [&str; CHANNEL_COUNT] = [
+    "switch", "color", "flow", "group", "pattern", "effect", "speed", "size", "scaling", "rotation", "hflip",
+    "vflip", "hmove", "vmove", "waves", "drawing",
+];
+
+const COMMANDS: [&str; 7] = ["fade", "dmx", "show", "blackout", "help", "quit", "exit"];
+
+// How often a fade writes a new value; about one DMX frame at 40 Hz.
+const FADE_STEP: Duration = Duration::from_millis(25);
+
+const HELP: &str = "\
+  ch7 200 | speed 200        set a laser channel (chN is relative to the laser's address)
+  color seg8 | color #ff0000 set an enum channel by name: switch on|off, color, group
+  fade ch8 0..255 3s         fade a channel; `fade size 40 500ms` fades from the current value
+  dmx 100 255                set an absolute universe channel
+  show                       print the laser's channels and what they decode to
+  blackout                   zero the whole universe
+  quit                       stop sending and leave";
+
+/// Variant names a channel accepts instead of a number, if any.
+fn variants(channel: usize) -> &'static [&'static str] {
+    match channel {
+        1 => &["off", "on"],
+        2 => &ColorMode::NAMES,
+        4 => &GraphicsGroup::NAMES,
+        _ => &[],
+    }
+}
+
+/// Laser channel (1-16) for `ch7` or `speed`.
+fn parse_channel(word: &str) -> Option<usize> {
+    if let Some(number) = word.strip_prefix("ch")
+        && let Ok(channel) = number.parse::<usize>()
+    {
+        return (1..=CHANNEL_COUNT).contains(&channel).then_some(channel);
+    }
+    CHANNEL_KEYWORDS.iter().position(|keyword| keyword.eq_ignore_ascii_case(word)).map(|index| index + 1)
+}
+
+fn parse_value(channel: usize, word: &str) -> Result<u8, Box<dyn Error>> {
+    if let Ok(value) = word.parse::<u8>() {
+        return Ok(value);
+    }
+    let value = match channel {
+        1 if word.eq_ignore_ascii_case("on") => Some(MainSwitch::On.to_u8()),
+        1 if word.eq_ignore_ascii_case("off") => Some(MainSwitch::Off.to_u8()),
+        2 => match word.strip_prefix('#').and_then(|hex| u32::from_str_radix(hex, 16).ok()) {
+            Some(rgb) if word.len() == 7 => {
+                Some(ColorMode::nearest_fixed(((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)).to_u8())
+            }
+            _ => ColorMode::from_name(word).map(ColorMode::to_u8),
+        },
+        4 => GraphicsGroup::from_name(word).map(GraphicsGroup::to_u8),
+        _ => None,
+    };
+    value.ok_or_else(|| {
+        let names = variants(channel);
+        if names.is_empty() {
+            format!("'{}' is not a DMX value (0-255)", word).into()
+        } else {
+            format!("'{}' is not 0-255 or one of {}", word, names.join(", ")).into()
+        }
+    })
+}
+
+/// `3s`, `500ms`, `1.5s` or plain seconds.
+fn parse_duration(word: &str) -> Result<Duration, Box<dyn Error>> {
+    let (number, scale) = match word.strip_suffix("ms") {
+        Some(ms) => (ms, 0.001),
+        None => (word.strip_suffix('s').unwrap_or(word), 1.0),
+    };
+    match number.parse::<f32>() {
+        Ok(value) if value >= 0.0 && value.is_finite() => Ok(Duration::from_secs_f32(value * scale)),
+        _ => Err(format!("'{}' is not a duration like 3s or 500ms", word).into()),
+    }
+}
+
+/// Applies shell commands to a running refresh loop.
+pub struct Shell {
+    handle: DmxHandle,
+    address: usize,
+    /// Bumped whenever a channel is written, so a fade still running on it stops.
+    generations: Arc<Vec<AtomicU64>>,
+}
+
+impl Shell {
+    pub fn new(handle: DmxHandle, address: usize) -> Self {
+        Shell { handle, address, generations: Arc::new((0..DMX_FRAME_SIZE).map(|_| AtomicU64::new(0)).collect()) }
+    }
+
+    fn set(&self, dmx_channel: usize, value: u8) {
+        self.generations[dmx_channel - 1].fetch_add(1, Ordering::SeqCst);
+        self.handle.set_channel(dmx_channel, value);
+    }
+
+    fn laser_channels(&self) -> [u8; CHANNEL_COUNT] {
+        let mut channels = [0u8; CHANNEL_COUNT];
+        for (index, value) in channels.iter_mut().enumerate() {
+            *value = self.handle.get_channel(self.address + index).unwrap_or(0);
+        }
+        channels
+    }
+
+    /// Runs one line. Returns text to print, or `None` when the user asked to quit.
+    pub fn execute(&mut self, line: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some(&command) = words.first() else {
+            return Ok(Some(String::new()));
+        };
+        match (command, &words[1..]) {
+            ("quit" | "exit", _) => Ok(None),
+            ("help", _) => Ok(Some(HELP.to_string())),
+            ("show", _) => Ok(Some(self.show())),
+            ("blackout", _) => {
+                for channel in 1..=DMX_FRAME_SIZE {
+                    self.set(channel, 0);
+                }
+                Ok(Some("blackout".to_string()))
+            }
+            ("dmx", [channel, value]) => {
+                let channel: usize = channel
+                    .parse()
+                    .ok()
+                    .filter(|channel| (1..=DMX_FRAME_SIZE).contains(channel))
+                    .ok_or_else(|| format!("'{}' is not a DMX channel (1-512)", channel))?;
+                let value: u8 = value.parse().map_err(|_| format!("'{}' is not a DMX value (0-255)", value))?;
+                self.set(channel, value);
+                Ok(Some(format!("dmx {} = {}", channel, value)))
+            }
+            ("fade", [channel, range, duration]) => self.fade(channel, range, duration),
+            (word, [value]) => {
+                let channel = parse_channel(word).ok_or_else(|| format!("unknown channel or command '{}'", word))?;
+                let value = parse_value(channel, value)?;
+                self.set(self.address + channel - 1, value);
+                Ok(Some(format!("ch{} {} = {}", channel, CHANNEL_NAMES[channel - 1], value)))
+            }
+            _ => Err(format!("can't parse '{}'; try help", line.trim()).into()),
+        }
+    }
+
+    fn fade(&self, channel: &str, range: &str, duration: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let laser_channel = parse_channel(channel).ok_or_else(|| format!("unknown channel '{}'", channel))?;
+        let dmx_channel = self.address + laser_channel - 1;
+        let (from, to) = match range.split_once("..") {
+            Some((from, to)) => (parse_value(laser_channel, from)?, parse_value(laser_channel, to)?),
+            None => (self.handle.get_channel(dmx_channel).unwrap_or(0), parse_value(laser_channel, range)?),
+        };
+        let duration = parse_duration(duration)?;
+
+        self.set(dmx_channel, from);
+        let generation = self.generations[dmx_channel - 1].load(Ordering::SeqCst);
+        let generations = self.generations.clone();
+        let handle = self.handle.clone();
+        thread::spawn(move || {
+            let started = Instant::now();
+            loop {
+                let progress = match duration.as_secs_f32() {
+                    total if total > 0.0 => (started.elapsed().as_secs_f32() / total).min(1.0),
+                    _ => 1.0,
+                };
+                if generations[dmx_channel - 1].load(Ordering::SeqCst) != generation {
+                    return; // Overridden by a newer command on this channel
+                }
+                let value = from as f32 + (to as f32 - from as f32) * progress;
+                handle.set_channel(dmx_channel, value.round() as u8);
+                if progress >= 1.0 {
+                    return;
+                }
+                thread::sleep(FADE_STEP);
+            }
+        });
+        Ok(Some(format!("fading ch{} {} {}..{} over {:?}", laser_channel, CHANNEL_NAMES[laser_channel - 1], from, to, duration)))
+    }
+
+    fn show(&self) -> String {
+        let channels = self.laser_channels();
+        let state = LaserState::from_channels(&channels);
+        let decoded = [
+            format!("{:?}", state.ch1),
+            format!("{:?} ({})", state.ch2, state.ch2.name()),
+            format!("{:?}", state.ch3),
+            format!("{:?} ({})", state.ch4, state.ch4.name()),
+            String::new(),
+            format!("{:?}", state.ch6),
+            String::new(),
+            String::new(),
+            format!("{:?}", state.ch9),
+            format!("{:?}", state.ch10),
+            format!("{:?}", state.ch11),
+            format!("{:?}", state.ch12),
+            format!("{:?}", state.ch13),
+            format!("{:?}", state.ch14),
+            format!("{:?}", state.ch15),
+            format!("{:?}", state.ch16),
+        ];
+        let lines: Vec<String> = (0..CHANNEL_COUNT)
+            .map(|i| {
+                let label = format!("ch{}", i + 1);
+                format!("{:<5} {:<9} {:>3}  {}", label, CHANNEL_KEYWORDS[i], channels[i], decoded[i]).trim_end().to_string()
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
+
+/// Completes commands and channel names on the first word, and variant names after an enum channel.
+pub struct ShellHelper;
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map(|i| i + 1).unwrap_or(0);
+        let partial = &line[start..];
+        let previous: Vec<&str> = line[..start].split_whitespace().collect();
+
+        let channel_words = || {
+            (1..=CHANNEL_COUNT).map(|channel| format!("ch{}", channel)).chain(CHANNEL_KEYWORDS.iter().map(|k| k.to_string()))
+        };
+        let options: Vec<String> = match previous.as_slice() {
+            [] => COMMANDS.iter().map(|c| c.to_string()).chain(channel_words()).collect(),
+            ["fade"] => channel_words().collect(),
+            [word] => parse_channel(word).map(|channel| variants(channel).iter().map(|v| v.to_string()).collect()).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        let candidates = options
+            .into_iter()
+            .filter(|option| option.starts_with(partial))
+            .map(|option| Pair { display: option.clone(), replacement: option })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+fn history_path() -> Option<PathBuf> {
+    AdapterAliases::default_path().and_then(|path| path.parent().map(|dir| dir.join("shell_history")))
+}
+
+/// Runs the shell until `quit` or end of input, refreshing the universe at `rate` Hz.
+pub fn run(controller: DmxController, address: usize, rate: f32) -> Result<(), Box<dyn Error>> {
+    let mut universe = DmxState::new(DMX_FRAME_SIZE);
+    super::place(&mut universe, address, &LaserState::new().to_channels())?;
+    let refresh = controller.start_refresh(universe, rate)?;
+    let mut shell = Shell::new(refresh.handle(), address);
+
+    let mut editor: Editor<ShellHelper, _> = Editor::new()?;
+    editor.set_helper(Some(ShellHelper));
+    let history = history_path();
+    if let Some(path) = &history {
+        let _ = editor.load_history(path);
+    }
+    println!("ZQ03268 at address {}, sending at {} Hz. Type help for commands.", address, rate);
+
+    loop {
+        let line = match editor.readline("laser> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        if !line.trim().is_empty() {
+            let _ = editor.add_history_entry(line.as_str());
+        }
+        match shell.execute(&line) {
+            Ok(Some(output)) if output.is_empty() => {}
+            Ok(Some(output)) => println!("{}", output),
+            Ok(None) => break,
+            Err(e) => println!("error: {}", e),
+        }
+        if let Some(e) = refresh.last_error() {
+            println!("output error: {}", e);
+        }
+    }
+
+    if let Some(path) = &history {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let _ = editor.save_history(path);
+    }
+    refresh.stop()?.close()
+}
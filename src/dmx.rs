@@ -1,15 +1,25 @@
 
 pub const DMX_BAUD_RATE: u32 = 250_000;
 pub const DMX_FRAME_SIZE: usize = 512;
-pub const DMX_TEST_FRAME: [u8; DMX_FRAME_SIZE] = [0u8; DMX_FRAME_SIZE];
 
-use serialport::{DataBits, FlowControl, Parity, StopBits};
-use std::thread;
-use std::time::Duration;
-use std::io::Write;
+// DMX512-A (ANSI E1.11) transmitter limits.
+pub const DMX_MIN_BREAK: Duration = Duration::from_micros(92);
+pub const DMX_MIN_MARK_AFTER_BREAK: Duration = Duration::from_micros(12);
+pub const DMX_MIN_BREAK_TO_BREAK: Duration = Duration::from_micros(1204);
+pub const DMX_MAX_INTER_FRAME_GAP: Duration = Duration::from_secs(1);
+/// One slot on the wire: start bit, 8 data bits and 2 stop bits at 4µs each.
+pub const DMX_SLOT_TIME: Duration = Duration::from_micros(44);
+
+use std::time::{Duration, Instant};
 use std::error::Error;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::transport::{AdapterAliases, DmxTransport, ReconnectingTransport, SerialAdapter};
 
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct DmxState {
     pub channels: Vec<u8>, // or [u8; 512] for a full DMX universe
 }
@@ -36,65 +46,294 @@ impl DmxState {
     }
 }
 
+/// Line timing of the frames a [`DmxController`] sends.
+///
+/// Break and mark-after-break only matter to transports that generate the
+/// break themselves (or that can be told how, like the Enttec widget).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DmxTiming {
+    pub break_time: Duration,
+    pub mark_after_break: Duration,
+    /// Idle line time between the end of one frame and the next break.
+    pub inter_frame_gap: Duration,
+    /// Number of slots transmitted after the start code (1-512).
+    pub slots: usize,
+}
+
+impl Default for DmxTiming {
+    fn default() -> Self {
+        DmxTiming {
+            break_time: Duration::from_micros(100),
+            mark_after_break: DMX_MIN_MARK_AFTER_BREAK,
+            inter_frame_gap: Duration::ZERO,
+            slots: DMX_FRAME_SIZE,
+        }
+    }
+}
+
+impl DmxTiming {
+    /// Checks the timing against the DMX512-A transmitter minimums.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.break_time < DMX_MIN_BREAK {
+            return Err(format!("DMX break must be at least {}µs", DMX_MIN_BREAK.as_micros()).into());
+        }
+        if self.mark_after_break < DMX_MIN_MARK_AFTER_BREAK {
+            return Err(format!("DMX mark-after-break must be at least {}µs", DMX_MIN_MARK_AFTER_BREAK.as_micros()).into());
+        }
+        if self.inter_frame_gap >= DMX_MAX_INTER_FRAME_GAP {
+            return Err("DMX inter-frame gap must be shorter than 1s".into());
+        }
+        if !(1..=DMX_FRAME_SIZE).contains(&self.slots) {
+            return Err("DMX frame must carry between 1 and 512 slots".into());
+        }
+        Ok(())
+    }
+
+    /// Break-to-break time of one frame, padded to the 1204µs DMX512-A minimum.
+    pub fn frame_period(&self) -> Duration {
+        let on_wire = self.break_time
+            + self.mark_after_break
+            + DMX_SLOT_TIME * (self.slots as u32 + 1)
+            + self.inter_frame_gap;
+        on_wire.max(DMX_MIN_BREAK_TO_BREAK)
+    }
+
+    pub fn max_refresh_rate(&self) -> f32 {
+        1.0 / self.frame_period().as_secs_f32()
+    }
+}
+
 pub struct DmxController {
-    port: Box<dyn serialport::SerialPort>,
-    address: usize,  // Starting channel (1-based)
+    transport: Box<dyn DmxTransport>,
+    address: usize,  // Starting channel (0-based)
+    timing: DmxTiming,
+    last_frame: Option<Instant>,
 }
 
 impl DmxController {
+    /// Opens a raw-serial ("Open DMX") adapter such as an FT232R cable.
     pub fn new(port_name: &str, address: usize) -> Result<Self, Box<dyn Error>> {
-        let mut port = serialport::new(port_name, DMX_BAUD_RATE)
-            .data_bits(DataBits::Eight)
-            .flow_control(FlowControl::None)
-            .parity(Parity::None)
-            .stop_bits(StopBits::Two)
-            .timeout(Duration::from_millis(10))
-            .open()?;
+        Self::with_adapter(port_name, SerialAdapter::OpenDmx, address)
+    }
 
-        Ok(DmxController { port, address: address - 1 })  // 0-based index
+    /// Opens a USB serial adapter of the given kind, e.g. an Enttec DMX USB Pro widget.
+    pub fn with_adapter(port_name: &str, adapter: SerialAdapter, address: usize) -> Result<Self, Box<dyn Error>> {
+        Self::with_transport(adapter.transport(port_name), address)
     }
 
-    pub fn send(&mut self, state: &DmxState) -> Result<(), Box<dyn Error>> {
-        self.port.set_break()?;
-        thread::sleep(Duration::from_micros(100));
-        self.port.clear_break()?;
+    /// Opens the adapter `selector` names (`COM4`, `serial:EN123456` or `alias:stage-left`,
+    /// see [`PortSelector`](crate::transport::PortSelector)) and reopens it if it is
+    /// unplugged and comes back, on whatever port it then appears as.
+    pub fn connect(selector: &str, address: usize) -> Result<Self, Box<dyn Error>> {
+        let transport = ReconnectingTransport::serial(selector.parse()?, AdapterAliases::load_default()?, None);
+        Self::with_transport(Box::new(transport), address)
+    }
 
-        let mut frame: Vec<u8> = vec![0x00];  // Start code
-        frame.resize(DMX_FRAME_SIZE + 1, 0);  // 1 + 512
+    /// Drives any [`DmxTransport`]; the transport is opened here.
+    pub fn with_transport(mut transport: Box<dyn DmxTransport>, address: usize) -> Result<Self, Box<dyn Error>> {
+        if !(1..=DMX_FRAME_SIZE).contains(&address) {
+            return Err("DMX address must be between 1 and 512".into());
+        }
+        transport.open()?;
+
+        Ok(DmxController { transport, address: address - 1, timing: DmxTiming::default(), last_frame: None })  // 0-based index
+    }
+
+    /// Changes break, mark-after-break, inter-frame gap and frame length. Fewer
+    /// slots means shorter frames, e.g. 16 slots for a single ZQ03268 at address 1.
+    pub fn set_timing(&mut self, timing: DmxTiming) -> Result<(), Box<dyn Error>> {
+        timing.validate()?;
+        self.transport.set_timing(&timing)?;
+        self.timing = timing;
+        Ok(())
+    }
+
+    pub fn timing(&self) -> DmxTiming {
+        self.timing
+    }
+
+    pub fn send(&mut self, state: &DmxState) -> Result<(), Box<dyn Error>> {
+        let slot_count = self.timing.slots.min(self.transport.capabilities().max_slots);
+        let mut slots = vec![0u8; slot_count];
 
         for (i, &val) in state.channels.iter().enumerate() {
-            let idx = self.address + i + 1;  // +1 for after start
-            if idx < frame.len() {
-                frame[idx] = val;
+            let idx = self.address + i;
+            if idx < slots.len() {
+                slots[idx] = val;
             }
         }
 
-        self.port.write_all(&frame)?;
-        Ok(())
+        // Don't start the next break before the previous frame and its gap are done.
+        if let Some(last_frame) = self.last_frame {
+            let ready = last_frame + self.timing.frame_period();
+            let now = Instant::now();
+            if ready > now {
+                thread::sleep(ready - now);
+            }
+        }
+        self.last_frame = Some(Instant::now());
+
+        self.transport.send_universe(&slots)
+    }
+
+    pub fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        self.transport.close()
+    }
+
+    /// Hands the controller to a background thread that retransmits `state` at
+    /// `rate_hz` until stopped. Fixtures such as the ZQ03268 black out when the
+    /// stream stops, so this is the normal way to drive a show.
+    ///
+    /// The rate is capped by the frame timing: about 44 Hz for 512 slots, more
+    /// with a shortened frame.
+    pub fn start_refresh(self, state: DmxState, rate_hz: f32) -> Result<DmxRefresh, Box<dyn Error>> {
+        let max_rate = self.timing.max_refresh_rate();
+        if !(rate_hz > 0.0 && rate_hz <= max_rate) {
+            return Err(format!("refresh rate must be between 0 and {:.1} Hz", max_rate).into());
+        }
+
+        let shared = Arc::new(RefreshShared {
+            state: Mutex::new(state),
+            sources: Mutex::new(Vec::new()),
+            running: AtomicBool::new(true),
+            frames_sent: AtomicU64::new(0),
+            last_error: Mutex::new(None),
+        });
+        let period = Duration::from_secs_f32(1.0 / rate_hz);
+        let thread_shared = shared.clone();
+        let thread = thread::Builder::new()
+            .name("dmx-refresh".to_string())
+            .spawn(move || refresh_loop(self, thread_shared, period))?;
+
+        Ok(DmxRefresh { shared, thread: Some(thread) })
     }
 }
 
-/// Returns a Vec of DMX-compatible serial port names (ports that can be opened at 250_000 baud, 2 stop bits, and accept a DMX frame).
-pub fn scan_dmx_ports() -> Vec<String> {
-	let mut dmx_ports = Vec::new();
-	match serialport::available_ports() {
-		Ok(ports) => {
-			for p in &ports {
-				println!("Testing port: {} (type: {:?}, info: {:?})", p.port_name, p.port_type, p); // Print port info
-				let result = serialport::new(&p.port_name, DMX_BAUD_RATE)
-					.timeout(Duration::from_millis(10))
-					.stop_bits(serialport::StopBits::Two)
-					.open()
-					.and_then(|mut port| {
-						port.write_all(&DMX_TEST_FRAME)
-							.map_err(|e| serialport::Error::new(serialport::ErrorKind::Io(std::io::ErrorKind::Other), format!("write failed: {}", e)))
-					});
-				if result.is_ok() {
-					dmx_ports.push(p.port_name.clone());
-				}
-			}
-		}
-		Err(_) => {}
-	}
-	dmx_ports
-}
\ No newline at end of file
+/// Computes part of the universe afresh for every frame, e.g. a cue
+/// [`Playback`](crate::playback::Playback). Attach with [`DmxHandle::add_source`].
+pub trait FrameSource: Send {
+    /// Renders the frame sent at `now` into `state`, which already holds the
+    /// handle's values and the output of sources added before this one.
+    fn render(&mut self, now: Instant, state: &mut DmxState);
+}
+
+struct RefreshShared {
+    state: Mutex<DmxState>,
+    sources: Mutex<Vec<Box<dyn FrameSource>>>,
+    running: AtomicBool,
+    frames_sent: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+fn refresh_loop(mut controller: DmxController, shared: Arc<RefreshShared>, period: Duration) -> DmxController {
+    let mut next_frame = Instant::now();
+    while shared.running.load(Ordering::Acquire) {
+        // Copy out so the lock is not held while the transport blocks.
+        let mut state = shared.state.lock().unwrap().clone();
+        let now = Instant::now();
+        for source in shared.sources.lock().unwrap().iter_mut() {
+            source.render(now, &mut state);
+        }
+        match controller.send(&state) {
+            Ok(()) => {
+                shared.frames_sent.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                *shared.last_error.lock().unwrap() = Some(e.to_string());
+            }
+        }
+
+        next_frame += period;
+        let now = Instant::now();
+        if next_frame > now {
+            thread::sleep(next_frame - now);
+        } else {
+            // Fell behind (slow transport or a stalled machine): don't burst to catch up.
+            next_frame = now;
+        }
+    }
+    controller
+}
+
+/// A [`DmxController`] running on its own refresh thread. Dropping it stops the thread.
+pub struct DmxRefresh {
+    shared: Arc<RefreshShared>,
+    thread: Option<JoinHandle<DmxController>>,
+}
+
+impl DmxRefresh {
+    /// A cloneable handle for changing the transmitted state from any thread.
+    pub fn handle(&self) -> DmxHandle {
+        DmxHandle { shared: self.shared.clone() }
+    }
+
+    pub fn frames_sent(&self) -> u64 {
+        self.shared.frames_sent.load(Ordering::Relaxed)
+    }
+
+    /// The most recent transmit error, if any frame has failed.
+    pub fn last_error(&self) -> Option<String> {
+        self.shared.last_error.lock().unwrap().clone()
+    }
+
+    /// Stops the thread after its current frame and hands the controller back.
+    pub fn stop(mut self) -> Result<DmxController, Box<dyn Error>> {
+        self.join().ok_or_else(|| "DMX refresh thread panicked".into())
+    }
+
+    fn join(&mut self) -> Option<DmxController> {
+        self.shared.running.store(false, Ordering::Release);
+        self.thread.take().and_then(|thread| thread.join().ok())
+    }
+}
+
+impl Drop for DmxRefresh {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+/// Shared access to the state a [`DmxRefresh`] keeps sending.
+#[derive(Clone)]
+pub struct DmxHandle {
+    shared: Arc<RefreshShared>,
+}
+
+impl DmxHandle {
+    pub fn set_channel(&self, channel: usize, value: u8) {
+        self.shared.state.lock().unwrap().set_channel(channel, value);
+    }
+
+    pub fn get_channel(&self, channel: usize) -> Option<u8> {
+        self.shared.state.lock().unwrap().get_channel(channel)
+    }
+
+    /// Replaces the whole state; the next frame picks it up.
+    pub fn set_state(&self, state: DmxState) {
+        *self.shared.state.lock().unwrap() = state;
+    }
+
+    pub fn state(&self) -> DmxState {
+        self.shared.state.lock().unwrap().clone()
+    }
+
+    /// Applies several changes atomically, so no frame carries half of them.
+    pub fn update<F: FnOnce(&mut DmxState)>(&self, f: F) {
+        f(&mut self.shared.state.lock().unwrap());
+    }
+
+    /// Renders `source` on top of the state for every frame from now on.
+    pub fn add_source(&self, source: Box<dyn FrameSource>) {
+        self.shared.sources.lock().unwrap().push(source);
+    }
+
+    /// Detaches all sources; frames carry just the handle's state again.
+    pub fn clear_sources(&self) {
+        self.shared.sources.lock().unwrap().clear();
+    }
+
+    /// Frames sent so far; still readable once the refresh has been stopped.
+    pub fn frames_sent(&self) -> u64 {
+        self.shared.frames_sent.load(Ordering::Relaxed)
+    }
+}
@@ -1,3 +1,726 @@
-pub fn test() {
-    println!("This is a test function in the ZQ03268 module.");
-}
\ No newline at end of file
+//! U'King ZQ03268 RGB animation laser, 16-channel DMX mode.
+//!
+//! Each channel with modes or sub-ranges in the manual has its own enum;
+//! [`LaserState`] collects all 16 and encodes them for a [`DmxController`](crate::dmx::DmxController).
+
+use std::error::Error;
+
+use crate::dmx::DmxState;
+use crate::fixture::{ofl, FadeBehaviour, Fixture, FixtureDefinition};
+
+pub const MANUFACTURER: &str = "U'King";
+
+/// The same channel map as an Open Fixture Library definition, for tools that
+/// work from fixture files rather than the typed model.
+pub const OFL_DEFINITION: &str = include_str!("ZQ03268.json");
+
+/// Number of DMX channels the fixture occupies.
+pub const CHANNEL_COUNT: usize = 16;
+
+/// Channel names as printed in the manual, CH1 first.
+pub const CHANNEL_NAMES: [&str; CHANNEL_COUNT] = [
+    "Main switch",
+    "Color mode",
+    "Color flow",
+    "Graphics group",
+    "Pattern select",
+    "Dynamic effect",
+    "Effect speed",
+    "Pattern size",
+    "Auto scaling",
+    "Rotation center",
+    "Horizontal flip",
+    "Vertical flip",
+    "Horizontal movement",
+    "Vertical movement",
+    "Waves X",
+    "Gradual drawing",
+];
+
+/// Runtime [`FixtureDefinition`] parsed from [`OFL_DEFINITION`].
+pub fn definition() -> FixtureDefinition {
+    ofl::parse(OFL_DEFINITION, MANUFACTURER).expect("bundled ZQ03268 definition is valid")
+}
+
+/// CH1: laser output on/off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MainSwitch {
+    Off,  // 0-9
+    On,   // 10-255
+}
+
+impl MainSwitch {
+    pub fn to_u8(self) -> u8 {
+        match self {
+            MainSwitch::Off => 0,
+            MainSwitch::On => 255,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0..=9 => MainSwitch::Off,
+            _ => MainSwitch::On,
+        }
+    }
+}
+
+/// CH2: colour selection and colour-change modes.
+///
+/// The manual only gives 0-69 as "fixed colours". The seven bands of 10 and
+/// their colour order are assumptions until checked against a fixture; see [`FIXED_COLORS`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+    FixedWhite,      // 0-9 (assumed band, manual only gives 0-69 for fixed colours)
+    FixedRed,        // 10-19 (assumed)
+    FixedGreen,      // 20-29 (assumed)
+    FixedBlue,       // 30-39 (assumed)
+    FixedYellow,     // 40-49 (assumed)
+    FixedCyan,       // 50-59 (assumed)
+    FixedPurple,     // 60-69 (assumed)
+    OverallChange,   // 70-79
+    PatternInitial,  // 80-89
+    Rainbow,         // 90-92
+    Seg2,            // 93-110
+    Seg3,            // 111-131
+    Seg4,            // 132-149
+    Seg8,            // 150-182
+    Seg16,           // 183-218
+    Seg32,           // 219-253
+    Gradient,        // 254-255
+}
+
+impl ColorMode {
+    pub fn to_u8(self) -> u8 {
+        match self {
+            // Start of each assumed band, as white and red always were
+            ColorMode::FixedWhite => 0,
+            ColorMode::FixedRed => 10,
+            ColorMode::FixedGreen => 20,
+            ColorMode::FixedBlue => 30,
+            ColorMode::FixedYellow => 40,
+            ColorMode::FixedCyan => 50,
+            ColorMode::FixedPurple => 60,
+            ColorMode::OverallChange => 75,
+            ColorMode::PatternInitial => 85,
+            ColorMode::Rainbow => 91,
+            ColorMode::Seg2 => 100,
+            ColorMode::Seg3 => 120,
+            ColorMode::Seg4 => 140,
+            ColorMode::Seg8 => 160,
+            ColorMode::Seg16 => 200,
+            ColorMode::Seg32 => 230,
+            ColorMode::Gradient => 255,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0..=9 => ColorMode::FixedWhite,
+            10..=19 => ColorMode::FixedRed,
+            20..=29 => ColorMode::FixedGreen,
+            30..=39 => ColorMode::FixedBlue,
+            40..=49 => ColorMode::FixedYellow,
+            50..=59 => ColorMode::FixedCyan,
+            60..=69 => ColorMode::FixedPurple,
+            70..=79 => ColorMode::OverallChange,
+            80..=89 => ColorMode::PatternInitial,
+            90..=92 => ColorMode::Rainbow,
+            93..=110 => ColorMode::Seg2,
+            111..=131 => ColorMode::Seg3,
+            132..=149 => ColorMode::Seg4,
+            150..=182 => ColorMode::Seg8,
+            183..=218 => ColorMode::Seg16,
+            219..=253 => ColorMode::Seg32,
+            254..=255 => ColorMode::Gradient,
+        }
+    }
+
+    /// Every mode in DMX order.
+    pub const ALL: [ColorMode; 17] = [
+        ColorMode::FixedWhite,
+        ColorMode::FixedRed,
+        ColorMode::FixedGreen,
+        ColorMode::FixedBlue,
+        ColorMode::FixedYellow,
+        ColorMode::FixedCyan,
+        ColorMode::FixedPurple,
+        ColorMode::OverallChange,
+        ColorMode::PatternInitial,
+        ColorMode::Rainbow,
+        ColorMode::Seg2,
+        ColorMode::Seg3,
+        ColorMode::Seg4,
+        ColorMode::Seg8,
+        ColorMode::Seg16,
+        ColorMode::Seg32,
+        ColorMode::Gradient,
+    ];
+
+    /// Short names for [`ColorMode::ALL`], as typed on a command line.
+    pub const NAMES: [&'static str; 17] = [
+        "white", "red", "green", "blue", "yellow", "cyan", "purple", "overall", "initial", "rainbow", "seg2",
+        "seg3", "seg4", "seg8", "seg16", "seg32", "gradient",
+    ];
+
+    pub fn name(self) -> &'static str {
+        let index = Self::ALL.iter().position(|&mode| mode == self).unwrap_or(0);
+        Self::NAMES[index]
+    }
+
+    /// Looks a mode up by its short name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .position(|candidate| candidate.eq_ignore_ascii_case(name))
+            .map(|index| Self::ALL[index])
+    }
+
+    /// Looks up a fixed colour by name ("red", "Cyan", ...).
+    pub fn fixed_by_name(name: &str) -> Option<Self> {
+        FIXED_COLORS
+            .iter()
+            .find(|color| color.name.eq_ignore_ascii_case(name))
+            .map(|color| color.mode)
+    }
+
+    /// The fixed colour closest in hue to `rgb`. Brightness is ignored because
+    /// the laser has no dimmer channel, so dark red still maps to red; black
+    /// maps to white.
+    pub fn nearest_fixed(rgb: (u8, u8, u8)) -> Self {
+        let (r, g, b) = rgb;
+        let peak = r.max(g).max(b);
+        if peak == 0 {
+            return ColorMode::FixedWhite;
+        }
+        let scale = |c: u8| c as i32 * 255 / peak as i32;
+        let target = (scale(r), scale(g), scale(b));
+
+        let distance = |color: &FixedColor| {
+            let (cr, cg, cb) = color.rgb;
+            let dr = cr as i32 - target.0;
+            let dg = cg as i32 - target.1;
+            let db = cb as i32 - target.2;
+            dr * dr + dg * dg + db * db
+        };
+        FIXED_COLORS.iter().min_by_key(|color| distance(color)).unwrap().mode
+    }
+
+    /// The fixed colour entry for this mode, if it is one.
+    pub fn fixed_color(self) -> Option<&'static FixedColor> {
+        FIXED_COLORS.iter().find(|color| color.mode == self)
+    }
+}
+
+/// One fixed colour on CH2 with its DMX band.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedColor {
+    pub mode: ColorMode,
+    pub name: &'static str,
+    pub min: u8,
+    pub max: u8,
+    /// Nominal colour of the beam, for matching requested colours.
+    pub rgb: (u8, u8, u8),
+}
+
+/// Every fixed colour the ZQ03268 offers on CH2, in DMX order. The bands are
+/// assumptions, not from the manual (see [`ColorMode`]).
+pub const FIXED_COLORS: [FixedColor; 7] = [
+    FixedColor { mode: ColorMode::FixedWhite, name: "white", min: 0, max: 9, rgb: (255, 255, 255) },
+    FixedColor { mode: ColorMode::FixedRed, name: "red", min: 10, max: 19, rgb: (255, 0, 0) },
+    FixedColor { mode: ColorMode::FixedGreen, name: "green", min: 20, max: 29, rgb: (0, 255, 0) },
+    FixedColor { mode: ColorMode::FixedBlue, name: "blue", min: 30, max: 39, rgb: (0, 0, 255) },
+    FixedColor { mode: ColorMode::FixedYellow, name: "yellow", min: 40, max: 49, rgb: (255, 255, 0) },
+    FixedColor { mode: ColorMode::FixedCyan, name: "cyan", min: 50, max: 59, rgb: (0, 255, 255) },
+    FixedColor { mode: ColorMode::FixedPurple, name: "purple", min: 60, max: 69, rgb: (255, 0, 255) },
+];
+
+/// CH3: colour flow direction and speed for the segmented colour modes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorFlow {
+    NoChange,             // 0-9
+    Forward(u8),          // 10-127: slow to fast
+    Reverse(u8),          // 128-255: slow to fast
+}
+
+impl ColorFlow {
+    pub fn to_u8(self) -> u8 {
+        match self {
+            ColorFlow::NoChange => 0,
+            ColorFlow::Forward(speed) => 10 + speed.clamp(0, 117),
+            ColorFlow::Reverse(speed) => 128 + speed.clamp(0, 127),
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0..=9 => ColorFlow::NoChange,
+            10..=127 => ColorFlow::Forward(value - 10),
+            128..=255 => ColorFlow::Reverse(value - 128),
+        }
+    }
+}
+
+/// CH4: pattern group; CH5 then selects a pattern within the group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphicsGroup {
+    Static1,  // 0-24: basic geo
+    Static2,  // 25-49
+    Static3,  // 50-74: edge highlight
+    Static4,  // 75-99: dot/punched
+    Static5,  // 100-124: Christmas
+    Animation1, // 125-149
+    Animation2, // 150-174
+    Animation3, // 175-199
+    Animation4, // 200-224
+    Animation5, // 225-255
+}
+
+impl GraphicsGroup {
+    pub fn to_u8(self) -> u8 {
+        match self {
+            GraphicsGroup::Static1 => 10,
+            GraphicsGroup::Static2 => 35,
+            GraphicsGroup::Static3 => 60,
+            GraphicsGroup::Static4 => 85,
+            GraphicsGroup::Static5 => 110,
+            GraphicsGroup::Animation1 => 135,
+            GraphicsGroup::Animation2 => 160,
+            GraphicsGroup::Animation3 => 185,
+            GraphicsGroup::Animation4 => 210,
+            GraphicsGroup::Animation5 => 235,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0..=24 => GraphicsGroup::Static1,
+            25..=49 => GraphicsGroup::Static2,
+            50..=74 => GraphicsGroup::Static3,
+            75..=99 => GraphicsGroup::Static4,
+            100..=124 => GraphicsGroup::Static5,
+            125..=149 => GraphicsGroup::Animation1,
+            150..=174 => GraphicsGroup::Animation2,
+            175..=199 => GraphicsGroup::Animation3,
+            200..=224 => GraphicsGroup::Animation4,
+            225..=255 => GraphicsGroup::Animation5,
+        }
+    }
+
+    /// Every group in DMX order.
+    pub const ALL: [GraphicsGroup; 10] = [
+        GraphicsGroup::Static1,
+        GraphicsGroup::Static2,
+        GraphicsGroup::Static3,
+        GraphicsGroup::Static4,
+        GraphicsGroup::Static5,
+        GraphicsGroup::Animation1,
+        GraphicsGroup::Animation2,
+        GraphicsGroup::Animation3,
+        GraphicsGroup::Animation4,
+        GraphicsGroup::Animation5,
+    ];
+
+    /// Short names for [`GraphicsGroup::ALL`].
+    pub const NAMES: [&'static str; 10] = [
+        "static1", "static2", "static3", "static4", "static5", "anim1", "anim2", "anim3", "anim4", "anim5",
+    ];
+
+    pub fn name(self) -> &'static str {
+        let index = Self::ALL.iter().position(|&group| group == self).unwrap_or(0);
+        Self::NAMES[index]
+    }
+
+    /// Looks a group up by its short name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .position(|candidate| candidate.eq_ignore_ascii_case(name))
+            .map(|index| Self::ALL[index])
+    }
+}
+
+// CH5: Pattern selection 0-255, raw u8
+
+/// CH6: built-in dynamic effects, overriding CH4/CH5 when active.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DynamicEffect {
+    None,                 // 0-1
+    Single(u8),           // 2-206: one per 2 values, so u8 1-102
+    LineRandom,           // 207-216
+    AnimationRandom,      // 217-226
+    ChristmasRandom,      // 227-236
+    OutdoorRandom,        // 237-246
+    AllRandom,            // 247-255
+}
+
+impl DynamicEffect {
+    pub fn to_u8(self) -> u8 {
+        match self {
+            DynamicEffect::None => 0,
+            DynamicEffect::Single(id) => 2 + (id.clamp(0, 102) * 2),
+            DynamicEffect::LineRandom => 210,
+            DynamicEffect::AnimationRandom => 220,
+            DynamicEffect::ChristmasRandom => 230,
+            DynamicEffect::OutdoorRandom => 240,
+            DynamicEffect::AllRandom => 250,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0..=1 => DynamicEffect::None,
+            2..=206 => DynamicEffect::Single((value - 2) / 2),
+            207..=216 => DynamicEffect::LineRandom,
+            217..=226 => DynamicEffect::AnimationRandom,
+            227..=236 => DynamicEffect::ChristmasRandom,
+            237..=246 => DynamicEffect::OutdoorRandom,
+            247..=255 => DynamicEffect::AllRandom,
+        }
+    }
+}
+
+// CH7: Effect speed 0-1 default, 2-255 slow-fast, raw u8 or enum Default / Manual(u8)
+
+// CH8: Pattern size 0-255 manual
+
+/// CH9: fixed size option or automatic zoom.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AutoScaling {
+    SizeOption(u8),       // 0-15
+    SmallToLarge(u8),     // 16-55: speed sel
+    LargeToSmall(u8),     // 56-95
+    ScalingSpeed(u8),     // 96-135
+    TwoPointIrregular,    // 136-175
+    ThreeQuarterIrregular,// 176-215
+    QuadraticIrregular,   // 216-255
+}
+
+impl AutoScaling {
+    pub fn to_u8(self) -> u8 {
+        match self {
+            AutoScaling::SizeOption(val) => val.clamp(0, 15),
+            AutoScaling::SmallToLarge(speed) => 16 + speed.clamp(0, 39),
+            AutoScaling::LargeToSmall(speed) => 56 + speed.clamp(0, 39),
+            AutoScaling::ScalingSpeed(speed) => 96 + speed.clamp(0, 39),
+            AutoScaling::TwoPointIrregular => 150,
+            AutoScaling::ThreeQuarterIrregular => 190,
+            AutoScaling::QuadraticIrregular => 230,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0..=15 => AutoScaling::SizeOption(value),
+            16..=55 => AutoScaling::SmallToLarge(value - 16),
+            56..=95 => AutoScaling::LargeToSmall(value - 56),
+            96..=135 => AutoScaling::ScalingSpeed(value - 96),
+            136..=175 => AutoScaling::TwoPointIrregular,
+            176..=215 => AutoScaling::ThreeQuarterIrregular,
+            216..=255 => AutoScaling::QuadraticIrregular,
+        }
+    }
+}
+
+/// CH10: rotation around the pattern centre.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RotationCenter {
+    Angle(u8),        // 0-127
+    ForwardSpeed(u8), // 128-191
+    ReverseSpeed(u8), // 192-255
+}
+
+impl RotationCenter {
+    pub fn to_u8(self) -> u8 {
+        match self {
+            RotationCenter::Angle(angle) => angle.clamp(0, 127),
+            RotationCenter::ForwardSpeed(speed) => 128 + speed.clamp(0, 63),
+            RotationCenter::ReverseSpeed(speed) => 192 + speed.clamp(0, 63),
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0..=127 => RotationCenter::Angle(value),
+            128..=191 => RotationCenter::ForwardSpeed(value - 128),
+            192..=255 => RotationCenter::ReverseSpeed(value - 192),
+        }
+    }
+}
+
+/// CH11/CH12: flip around the X or Y axis, as a fixed position or a continuous speed.
+// The manual calls CH11 "horizontal flip" but describes it as a rotation around the X-axis
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlipHorizontal {
+    Position(u8),  // 0-127
+    Speed(u8),     // 128-255
+}
+
+impl FlipHorizontal {
+    pub fn to_u8(self) -> u8 {
+        match self {
+            FlipHorizontal::Position(pos) => pos.clamp(0, 127),
+            FlipHorizontal::Speed(speed) => 128 + speed.clamp(0, 127),
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0..=127 => FlipHorizontal::Position(value),
+            128..=255 => FlipHorizontal::Speed(value - 128),
+        }
+    }
+}
+
+// CH12: Vertical flip, same as above
+
+/// CH13/CH14: position or circular movement along one axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MovementHorizontal {
+    Position(u8),         // 0-127
+    CircularSpeed(u8),    // 128-255
+}
+
+impl MovementHorizontal {
+    pub fn to_u8(self) -> u8 {
+        match self {
+            MovementHorizontal::Position(pos) => pos.clamp(0, 127),
+            MovementHorizontal::CircularSpeed(speed) => 128 + speed.clamp(0, 127),
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0..=127 => MovementHorizontal::Position(value),
+            128..=255 => MovementHorizontal::CircularSpeed(value - 128),
+        }
+    }
+}
+
+// CH14: Vertical movement, same
+
+/// CH15: wave distortion amplitude/speed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WavesX {
+    None,  // 0-1
+    AmpSpeed(u8),  // 2-255: 8 gears, every 32
+}
+
+impl WavesX {
+    pub fn to_u8(self) -> u8 {
+        match self {
+            WavesX::None => 0,
+            WavesX::AmpSpeed(gear) => 2 + (gear.clamp(0, 7) * 32),
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0..=1 => WavesX::None,
+            2..=255 => WavesX::AmpSpeed((value - 2) / 32),
+        }
+    }
+}
+
+/// CH16: gradual drawing of the pattern outline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradualDrawing {
+    None,                 // 0-1
+    Manual1,              // 2-63
+    Manual2,              // 64-127
+    AutoClockwise(u8),    // 128-153: slow-fast
+    AutoCounter(u8),      // 154-179
+    AutoIncDecReverse,    // 180-205
+    AutoIncDecSame,       // 206-255
+}
+
+impl GradualDrawing {
+    pub fn to_u8(self) -> u8 {
+        match self {
+            GradualDrawing::None => 0,
+            GradualDrawing::Manual1 => 30,
+            GradualDrawing::Manual2 => 90,
+            GradualDrawing::AutoClockwise(speed) => 128 + speed.clamp(0, 25),
+            GradualDrawing::AutoCounter(speed) => 154 + speed.clamp(0, 25),
+            GradualDrawing::AutoIncDecReverse => 190,
+            GradualDrawing::AutoIncDecSame => 220,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0..=1 => GradualDrawing::None,
+            2..=63 => GradualDrawing::Manual1,
+            64..=127 => GradualDrawing::Manual2,
+            128..=153 => GradualDrawing::AutoClockwise(value - 128),
+            154..=179 => GradualDrawing::AutoCounter(value - 154),
+            180..=205 => GradualDrawing::AutoIncDecReverse,
+            206..=255 => GradualDrawing::AutoIncDecSame,
+        }
+    }
+}
+
+/// Complete state of one ZQ03268 in its 16-channel DMX mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LaserState {
+    pub ch1: MainSwitch,
+    pub ch2: ColorMode,
+    pub ch3: ColorFlow,
+    pub ch4: GraphicsGroup,
+    pub ch5: u8,  // Pattern select 0-255
+    pub ch6: DynamicEffect,
+    pub ch7: u8,  // Speed 0-255
+    pub ch8: u8,  // Size 0-255
+    pub ch9: AutoScaling,
+    pub ch10: RotationCenter,
+    pub ch11: FlipHorizontal,  // Around X
+    pub ch12: FlipHorizontal,  // Around Y, same type
+    pub ch13: MovementHorizontal,
+    pub ch14: MovementHorizontal,  // Vertical, same type
+    pub ch15: WavesX,
+    pub ch16: GradualDrawing,
+}
+
+impl Default for LaserState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LaserState {
+    pub fn new() -> Self {
+        LaserState {
+            ch1: MainSwitch::Off,
+            ch2: ColorMode::FixedWhite,
+            ch3: ColorFlow::NoChange,
+            ch4: GraphicsGroup::Static1,
+            ch5: 0,
+            ch6: DynamicEffect::None,
+            ch7: 0,
+            ch8: 128,  // Mid size
+            ch9: AutoScaling::SizeOption(0),
+            ch10: RotationCenter::Angle(0),
+            ch11: FlipHorizontal::Position(0),
+            ch12: FlipHorizontal::Position(0),
+            ch13: MovementHorizontal::Position(64),  // Center-ish
+            ch14: MovementHorizontal::Position(64),
+            ch15: WavesX::None,
+            ch16: GradualDrawing::None,
+        }
+    }
+
+    /// Encodes the state as the 16 DMX values for CH1..CH16.
+    pub fn to_channels(&self) -> [u8; CHANNEL_COUNT] {
+        [
+            self.ch1.to_u8(),
+            self.ch2.to_u8(),
+            self.ch3.to_u8(),
+            self.ch4.to_u8(),
+            self.ch5,
+            self.ch6.to_u8(),
+            self.ch7,
+            self.ch8,
+            self.ch9.to_u8(),
+            self.ch10.to_u8(),
+            self.ch11.to_u8(),
+            self.ch12.to_u8(),
+            self.ch13.to_u8(),
+            self.ch14.to_u8(),
+            self.ch15.to_u8(),
+            self.ch16.to_u8(),
+        ]
+    }
+
+    /// Decodes 16 DMX values (CH1..CH16) back into typed channel settings.
+    /// Every byte maps to some variant; values inside a range decode to the
+    /// variant owning that range, so re-encoding may normalise them.
+    pub fn from_channels(channels: &[u8; CHANNEL_COUNT]) -> Self {
+        LaserState {
+            ch1: MainSwitch::from_u8(channels[0]),
+            ch2: ColorMode::from_u8(channels[1]),
+            ch3: ColorFlow::from_u8(channels[2]),
+            ch4: GraphicsGroup::from_u8(channels[3]),
+            ch5: channels[4],
+            ch6: DynamicEffect::from_u8(channels[5]),
+            ch7: channels[6],
+            ch8: channels[7],
+            ch9: AutoScaling::from_u8(channels[8]),
+            ch10: RotationCenter::from_u8(channels[9]),
+            ch11: FlipHorizontal::from_u8(channels[10]),
+            ch12: FlipHorizontal::from_u8(channels[11]),
+            ch13: MovementHorizontal::from_u8(channels[12]),
+            ch14: MovementHorizontal::from_u8(channels[13]),
+            ch15: WavesX::from_u8(channels[14]),
+            ch16: GradualDrawing::from_u8(channels[15]),
+        }
+    }
+
+    /// Decodes the laser patched at `address` (1-based) from a universe, e.g. a
+    /// frame captured from a console. `None` if the 16 channels don't fit.
+    pub fn from_dmx_state(state: &DmxState, address: usize) -> Option<Self> {
+        let start = address.checked_sub(1)?;
+        let slice = state.channels.get(start..start + CHANNEL_COUNT)?;
+        let mut channels = [0u8; CHANNEL_COUNT];
+        channels.copy_from_slice(slice);
+        Some(Self::from_channels(&channels))
+    }
+
+    /// The 16 channels as a [`DmxState`], ready for [`DmxController::send`](crate::dmx::DmxController::send)
+    /// with the controller addressed at the laser's DMX start address.
+    pub fn to_dmx_state(&self) -> DmxState {
+        DmxState {
+            channels: self.to_channels().to_vec(),
+        }
+    }
+}
+
+impl From<&LaserState> for DmxState {
+    fn from(state: &LaserState) -> Self {
+        state.to_dmx_state()
+    }
+}
+
+impl Fixture for LaserState {
+    fn footprint(&self) -> usize {
+        CHANNEL_COUNT
+    }
+
+    fn channel_names(&self) -> Vec<String> {
+        CHANNEL_NAMES.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn encode(&self, slots: &mut [u8]) {
+        slots.copy_from_slice(&self.to_channels());
+    }
+
+    fn decode(&mut self, slots: &[u8]) -> Result<(), Box<dyn Error>> {
+        let channels: &[u8; CHANNEL_COUNT] = slots
+            .try_into()
+            .map_err(|_| format!("ZQ03268 needs {} channels, got {}", CHANNEL_COUNT, slots.len()))?;
+        *self = LaserState::from_channels(channels);
+        Ok(())
+    }
+
+    /// Sizes, speeds and positions fade; colour, pattern and effect selectors snap.
+    fn fade_behaviours(&self) -> Vec<FadeBehaviour> {
+        use FadeBehaviour::{Fade, FadeWithin, Snap};
+        vec![
+            Snap,                                            // CH1 on/off
+            Snap,                                            // CH2 colour mode
+            FadeWithin(vec![10..=127, 128..=255]),           // CH3 flow speed per direction
+            Snap,                                            // CH4 pattern group
+            Snap,                                            // CH5 pattern
+            Snap,                                            // CH6 dynamic effect
+            FadeWithin(vec![0..=1, 2..=255]),                // CH7 effect speed
+            Fade,                                            // CH8 size
+            FadeWithin(vec![16..=55, 56..=95, 96..=135]),    // CH9 zoom speeds; size options snap
+            FadeWithin(vec![0..=127, 128..=191, 192..=255]), // CH10 angle or speed
+            FadeWithin(vec![0..=127, 128..=255]),            // CH11 flip position or speed
+            FadeWithin(vec![0..=127, 128..=255]),            // CH12
+            FadeWithin(vec![0..=127, 128..=255]),            // CH13 position or circular speed
+            FadeWithin(vec![0..=127, 128..=255]),            // CH14
+            Snap,                                            // CH15 wave gears
+            Snap,                                            // CH16 drawing modes
+        ]
+    }
+}
@@ -1 +1,2 @@
-pub mod ZQ03268;
\ No newline at end of file
+#[allow(non_snake_case)]
+pub mod ZQ03268;
@@ -0,0 +1,312 @@
+use serde_json::{Map, Value};
+use std::error::Error;
+use std::sync::Arc;
+
+use super::{FadeBehaviour, Fixture};
+use crate::dmx::DmxState;
+
+/// A DMX value range on a channel with one meaning, e.g. "Rainbow" at 90-92.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Capability {
+    pub min: u8,
+    pub max: u8,
+    /// What kind of function this is, using Open Fixture Library capability
+    /// types ("Effect", "ColorPreset", "ShutterStrobe", ...).
+    pub kind: String,
+    /// Human-readable name, unique within the channel.
+    pub name: String,
+    /// Open Fixture Library properties laserport doesn't interpret
+    /// (`shutterEffect`, `colors`, `speedStart`, ...), kept so exports match the source.
+    pub properties: Map<String, Value>,
+}
+
+impl Capability {
+    pub fn new(min: u8, max: u8, kind: &str, name: &str) -> Self {
+        Capability { min, max, kind: kind.to_string(), name: name.to_string(), properties: Map::new() }
+    }
+
+    pub fn contains(&self, value: u8) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    /// The middle of the range, which any receiver decodes as this capability.
+    pub fn center(&self) -> u8 {
+        ((self.min as u16 + self.max as u16) / 2) as u8
+    }
+
+    /// Value at `fraction` (0.0-1.0) through the range, for speed or position capabilities.
+    /// A backwards range (min above max) only has `min`.
+    pub fn value_at(&self, fraction: f32) -> u8 {
+        let span = self.max.saturating_sub(self.min) as f32;
+        self.min + (fraction.clamp(0.0, 1.0) * span).round() as u8
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelDefinition {
+    pub name: String,
+    pub default_value: u8,
+    /// Capabilities in DMX order, covering 0-255 without overlap.
+    pub capabilities: Vec<Capability>,
+    /// Names of this channel's fine (16-bit, 24-bit, ...) channels, each also defined as a plain channel.
+    pub fine_channel_aliases: Vec<String>,
+}
+
+impl ChannelDefinition {
+    /// Looks a capability up by name, ignoring case.
+    pub fn capability(&self, name: &str) -> Option<&Capability> {
+        self.capabilities.iter().find(|cap| cap.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn capability_at(&self, value: u8) -> Option<&Capability> {
+        self.capabilities.iter().find(|cap| cap.contains(value))
+    }
+
+    /// Fades within capabilities of a continuous kind (position, speed, size, ...)
+    /// and snaps everywhere else, so a crossfade never passes through presets.
+    pub fn fade_behaviour(&self) -> FadeBehaviour {
+        let ranges: Vec<_> = self
+            .capabilities
+            .iter()
+            .filter(|cap| cap.min < cap.max && CONTINUOUS_KINDS.contains(&cap.kind.as_str()))
+            .map(|cap| cap.min..=cap.max)
+            .collect();
+        match ranges.as_slice() {
+            [] => FadeBehaviour::Snap,
+            [range] if *range == (0..=255) => FadeBehaviour::Fade,
+            _ => FadeBehaviour::FadeWithin(ranges),
+        }
+    }
+}
+
+/// Open Fixture Library capability types whose DMX range is a scale rather than a list of presets.
+const CONTINUOUS_KINDS: [&str; 16] = [
+    "Intensity",
+    "ColorIntensity",
+    "Pan",
+    "PanContinuous",
+    "Tilt",
+    "TiltContinuous",
+    "Speed",
+    "EffectSpeed",
+    "EffectDuration",
+    "Zoom",
+    "Focus",
+    "Iris",
+    "Rotation",
+    "PrismRotation",
+    "WheelRotation",
+    "BeamAngle",
+];
+
+/// One DMX personality of a fixture: which channels it uses, in order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModeDefinition {
+    pub name: String,
+    pub short_name: Option<String>,
+    /// Channel names, CH1 first; each names a [`ChannelDefinition`].
+    pub channels: Vec<String>,
+}
+
+/// Physical data about the fixture, all optional as files often leave it out.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Physical {
+    /// Width, height, depth in millimetres.
+    pub dimensions_mm: Option<[f32; 3]>,
+    pub weight_kg: Option<f32>,
+    pub power_w: Option<f32>,
+    pub dmx_connector: Option<String>,
+    /// Light source type, e.g. "Laser" or "LED".
+    pub bulb: Option<String>,
+}
+
+/// Who wrote a fixture file and when, as Open Fixture Library records it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Meta {
+    pub authors: Vec<String>,
+    /// YYYY-MM-DD.
+    pub create_date: Option<String>,
+    pub last_modify_date: Option<String>,
+}
+
+/// Fixture description loaded at runtime (Open Fixture Library, QLC+, ...)
+/// rather than modelled in Rust like [`ZQ03268`](crate::dmxcharts::ZQ03268).
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixtureDefinition {
+    pub manufacturer: String,
+    pub name: String,
+    pub categories: Vec<String>,
+    pub physical: Physical,
+    /// Empty unless the source format has it (QLC+ doesn't).
+    pub meta: Meta,
+    pub channels: Vec<ChannelDefinition>,
+    pub modes: Vec<ModeDefinition>,
+}
+
+impl FixtureDefinition {
+    pub fn channel(&self, name: &str) -> Option<&ChannelDefinition> {
+        self.channels.iter().find(|channel| channel.name == name)
+    }
+
+    /// Looks a mode up by name or short name.
+    pub fn mode(&self, name: &str) -> Option<&ModeDefinition> {
+        self.modes
+            .iter()
+            .find(|mode| mode.name == name || mode.short_name.as_deref() == Some(name))
+    }
+
+    /// Checks that modes and fine channel aliases only name defined channels and capabilities don't overlap.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.modes.is_empty() {
+            return Err(format!("fixture '{}' has no modes", self.name).into());
+        }
+        for mode in &self.modes {
+            for channel in &mode.channels {
+                if self.channel(channel).is_none() {
+                    return Err(format!("mode '{}' uses undefined channel '{}'", mode.name, channel).into());
+                }
+            }
+        }
+        for channel in &self.channels {
+            for pair in channel.capabilities.windows(2) {
+                if pair[1].min <= pair[0].max {
+                    return Err(format!(
+                        "channel '{}': capability '{}' overlaps '{}'",
+                        channel.name, pair[1].name, pair[0].name
+                    )
+                    .into());
+                }
+            }
+            if channel.capabilities.iter().any(|cap| cap.min > cap.max) {
+                return Err(format!("channel '{}' has an empty capability range", channel.name).into());
+            }
+            for alias in &channel.fine_channel_aliases {
+                if self.channel(alias).is_none() {
+                    return Err(format!("channel '{}' has undefined fine channel '{}'", channel.name, alias).into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A fixture driven through a [`FixtureDefinition`] in one of its modes.
+#[derive(Clone, Debug)]
+pub struct DefinedFixture {
+    definition: Arc<FixtureDefinition>,
+    mode: usize,
+    values: Vec<u8>,
+}
+
+impl DefinedFixture {
+    /// Creates the fixture in `mode` with every channel at its default value.
+    pub fn new(definition: Arc<FixtureDefinition>, mode: &str) -> Result<Self, Box<dyn Error>> {
+        let mode_index = definition
+            .modes
+            .iter()
+            .position(|m| m.name == mode || m.short_name.as_deref() == Some(mode))
+            .ok_or_else(|| format!("fixture '{}' has no mode '{}'", definition.name, mode))?;
+
+        let mut values = Vec::new();
+        for name in &definition.modes[mode_index].channels {
+            let channel = definition
+                .channel(name)
+                .ok_or_else(|| format!("mode '{}' uses undefined channel '{}'", mode, name))?;
+            values.push(channel.default_value);
+        }
+        Ok(DefinedFixture { definition, mode: mode_index, values })
+    }
+
+    pub fn definition(&self) -> &FixtureDefinition {
+        &self.definition
+    }
+
+    pub fn mode(&self) -> &ModeDefinition {
+        &self.definition.modes[self.mode]
+    }
+
+    pub fn values(&self) -> &[u8] {
+        &self.values
+    }
+
+    fn channel_index(&self, channel: &str) -> Result<usize, Box<dyn Error>> {
+        self.mode()
+            .channels
+            .iter()
+            .position(|name| name == channel)
+            .ok_or_else(|| format!("mode '{}' has no channel '{}'", self.mode().name, channel).into())
+    }
+
+    pub fn get(&self, channel: &str) -> Option<u8> {
+        self.channel_index(channel).ok().map(|index| self.values[index])
+    }
+
+    pub fn set(&mut self, channel: &str, value: u8) -> Result<(), Box<dyn Error>> {
+        let index = self.channel_index(channel)?;
+        self.values[index] = value;
+        Ok(())
+    }
+
+    fn lookup_capability(&self, channel: &str, capability: &str) -> Result<(usize, Capability), Box<dyn Error>> {
+        let index = self.channel_index(channel)?;
+        let definition = self.definition.channel(channel).ok_or("channel is not defined")?;
+        let cap = definition
+            .capability(capability)
+            .ok_or_else(|| format!("channel '{}' has no capability '{}'", channel, capability))?;
+        Ok((index, cap.clone()))
+    }
+
+    /// Sets `channel` to the middle of the named capability, e.g. ("Color mode", "Rainbow").
+    pub fn set_capability(&mut self, channel: &str, capability: &str) -> Result<(), Box<dyn Error>> {
+        let (index, cap) = self.lookup_capability(channel, capability)?;
+        self.values[index] = cap.center();
+        Ok(())
+    }
+
+    /// Sets `channel` to `fraction` (0.0-1.0) through the named capability's range.
+    pub fn set_capability_at(&mut self, channel: &str, capability: &str, fraction: f32) -> Result<(), Box<dyn Error>> {
+        let (index, cap) = self.lookup_capability(channel, capability)?;
+        self.values[index] = cap.value_at(fraction);
+        Ok(())
+    }
+
+    /// The capability `channel` is currently in.
+    pub fn capability(&self, channel: &str) -> Option<&Capability> {
+        let value = self.get(channel)?;
+        self.definition.channel(channel)?.capability_at(value)
+    }
+
+    pub fn to_dmx_state(&self) -> DmxState {
+        DmxState { channels: self.values.clone() }
+    }
+}
+
+impl Fixture for DefinedFixture {
+    fn footprint(&self) -> usize {
+        self.values.len()
+    }
+
+    fn channel_names(&self) -> Vec<String> {
+        self.mode().channels.clone()
+    }
+
+    fn encode(&self, slots: &mut [u8]) {
+        slots.copy_from_slice(&self.values);
+    }
+
+    fn decode(&mut self, slots: &[u8]) -> Result<(), Box<dyn Error>> {
+        if slots.len() != self.values.len() {
+            return Err(format!("expected {} channels, got {}", self.values.len(), slots.len()).into());
+        }
+        self.values.copy_from_slice(slots);
+        Ok(())
+    }
+
+    fn fade_behaviours(&self) -> Vec<FadeBehaviour> {
+        self.mode()
+            .channels
+            .iter()
+            .map(|name| self.definition.channel(name).map(ChannelDefinition::fade_behaviour).unwrap_or(FadeBehaviour::Fade))
+            .collect()
+    }
+}
@@ -0,0 +1,105 @@
+use std::error::Error;
+use std::ops::RangeInclusive;
+
+pub mod definition;
+pub mod ofl;
+pub mod qxf;
+
+pub use definition::{Capability, ChannelDefinition, DefinedFixture, FixtureDefinition, Meta, ModeDefinition, Physical};
+
+/// Anything that occupies a block of consecutive DMX channels.
+///
+/// Implementations own their current values; a [`Patch`](crate::patch::Patch)
+/// places them at an address and renders the universe.
+pub trait Fixture: Send {
+    /// Number of consecutive DMX channels the fixture occupies.
+    fn footprint(&self) -> usize;
+
+    /// Name of each channel, CH1 first; `footprint()` entries.
+    fn channel_names(&self) -> Vec<String>;
+
+    /// Writes the current values into `slots`, which is `footprint()` long.
+    fn encode(&self, slots: &mut [u8]);
+
+    /// Replaces the current values with the ones in `slots` (`footprint()` long).
+    fn decode(&mut self, slots: &[u8]) -> Result<(), Box<dyn Error>>;
+
+    /// How each channel behaves in a crossfade, CH1 first; `footprint()` entries.
+    /// Every channel fades unless the fixture says otherwise.
+    fn fade_behaviours(&self) -> Vec<FadeBehaviour> {
+        vec![FadeBehaviour::Fade; self.footprint()]
+    }
+}
+
+/// How a channel gets from one value to another during a crossfade.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FadeBehaviour {
+    /// Interpolate, e.g. a size or dimmer channel.
+    Fade,
+    /// Jump to the new value at the fade's snap point, e.g. a pattern selector
+    /// whose in-between values are other patterns.
+    Snap,
+    /// Interpolate while both values are in the same one of these ranges (a
+    /// position or speed range), snap otherwise.
+    FadeWithin(Vec<RangeInclusive<u8>>),
+}
+
+impl FadeBehaviour {
+    /// Whether a fade from `from` to `to` interpolates rather than snaps.
+    pub fn fades(&self, from: u8, to: u8) -> bool {
+        match self {
+            FadeBehaviour::Fade => true,
+            FadeBehaviour::Snap => false,
+            FadeBehaviour::FadeWithin(ranges) => {
+                ranges.iter().any(|range| range.contains(&from) && range.contains(&to))
+            }
+        }
+    }
+
+    /// The value `progress` (0.0-1.0) of the way from `from` to `to`. A snapping
+    /// channel switches once `progress` reaches `snap_at`.
+    pub fn value_at(&self, from: u8, to: u8, progress: f32, snap_at: f32) -> u8 {
+        let progress = progress.clamp(0.0, 1.0);
+        if self.fades(from, to) {
+            (from as f32 + (to as f32 - from as f32) * progress).round() as u8
+        } else if progress >= snap_at {
+            to
+        } else {
+            from
+        }
+    }
+}
+
+/// A fixture with no model: just `footprint` raw channel values, e.g. a dimmer pack.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawFixture {
+    pub values: Vec<u8>,
+}
+
+impl RawFixture {
+    pub fn new(footprint: usize) -> Self {
+        RawFixture { values: vec![0u8; footprint] }
+    }
+}
+
+impl Fixture for RawFixture {
+    fn footprint(&self) -> usize {
+        self.values.len()
+    }
+
+    fn channel_names(&self) -> Vec<String> {
+        (1..=self.values.len()).map(|ch| format!("CH{}", ch)).collect()
+    }
+
+    fn encode(&self, slots: &mut [u8]) {
+        slots.copy_from_slice(&self.values);
+    }
+
+    fn decode(&mut self, slots: &[u8]) -> Result<(), Box<dyn Error>> {
+        if slots.len() != self.values.len() {
+            return Err(format!("expected {} channels, got {}", self.values.len(), slots.len()).into());
+        }
+        self.values.copy_from_slice(slots);
+        Ok(())
+    }
+}
@@ -0,0 +1,324 @@
+//! Open Fixture Library fixture files (`fixtures/<manufacturer>/<fixture>.json`).
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use super::definition::{Capability, ChannelDefinition, FixtureDefinition, Meta, ModeDefinition, Physical};
+
+pub const SCHEMA_URL: &str =
+    "https://raw.githubusercontent.com/OpenLightingProject/open-fixture-library/master/schemas/fixture.json";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OflFixture {
+    name: String,
+    #[serde(default)]
+    categories: Vec<String>,
+    #[serde(default)]
+    meta: OflMeta,
+    #[serde(default)]
+    physical: OflPhysical,
+    available_channels: serde_json::Map<String, Value>,
+    modes: Vec<OflMode>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct OflMeta {
+    #[serde(default)]
+    authors: Vec<String>,
+    create_date: Option<String>,
+    last_modify_date: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct OflPhysical {
+    dimensions: Option<[f32; 3]>,
+    weight: Option<f32>,
+    power: Option<f32>,
+    #[serde(rename = "DMXconnector")]
+    dmx_connector: Option<String>,
+    bulb: Option<OflBulb>,
+}
+
+#[derive(Deserialize)]
+struct OflBulb {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OflChannel {
+    #[serde(default)]
+    default_value: Option<Value>,
+    #[serde(default)]
+    fine_channel_aliases: Vec<String>,
+    capability: Option<OflCapability>,
+    capabilities: Option<Vec<OflCapability>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OflCapability {
+    dmx_range: Option<[u16; 2]>,
+    #[serde(rename = "type")]
+    kind: String,
+    comment: Option<String>,
+    effect_name: Option<String>,
+    #[serde(flatten)]
+    properties: serde_json::Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OflMode {
+    name: String,
+    short_name: Option<String>,
+    channels: Vec<Value>,
+}
+
+/// Parses an OFL fixture file. OFL keeps the manufacturer in the directory
+/// name rather than the file, so it is passed in.
+///
+/// Matrix fixtures (template channels, `null` or object mode entries) and
+/// 16-bit capability ranges are not supported; fine channels are loaded as
+/// plain 0-255 channels.
+pub fn parse(json: &str, manufacturer: &str) -> Result<FixtureDefinition, Box<dyn Error>> {
+    let fixture: OflFixture = serde_json::from_str(json)?;
+
+    let mut channels = Vec::new();
+    for (name, channel) in fixture.available_channels {
+        let channel: OflChannel = serde_json::from_value(channel)
+            .map_err(|e| format!("channel '{}': {}", name, e))?;
+        channels.push(ChannelDefinition {
+            name: name.clone(),
+            default_value: parse_default_value(channel.default_value.as_ref())
+                .map_err(|e| format!("channel '{}': {}", name, e))?,
+            capabilities: parse_capabilities(&name, channel.capability, channel.capabilities)?,
+            fine_channel_aliases: channel.fine_channel_aliases.clone(),
+        });
+        for alias in channel.fine_channel_aliases {
+            channels.push(ChannelDefinition {
+                name: alias,
+                default_value: 0,
+                capabilities: vec![Capability::new(0, 255, "Generic", "Fine")],
+                fine_channel_aliases: Vec::new(),
+            });
+        }
+    }
+
+    let mut modes = Vec::new();
+    for mode in fixture.modes {
+        let mut mode_channels = Vec::new();
+        for channel in &mode.channels {
+            match channel {
+                Value::String(name) => mode_channels.push(name.clone()),
+                _ => return Err(format!("mode '{}': only plain channel references are supported", mode.name).into()),
+            }
+        }
+        modes.push(ModeDefinition { name: mode.name, short_name: mode.short_name, channels: mode_channels });
+    }
+
+    let definition = FixtureDefinition {
+        manufacturer: manufacturer.to_string(),
+        name: fixture.name,
+        categories: fixture.categories,
+        physical: Physical {
+            dimensions_mm: fixture.physical.dimensions,
+            weight_kg: fixture.physical.weight,
+            power_w: fixture.physical.power,
+            dmx_connector: fixture.physical.dmx_connector,
+            bulb: fixture.physical.bulb.and_then(|bulb| bulb.kind),
+        },
+        meta: Meta {
+            authors: fixture.meta.authors,
+            create_date: fixture.meta.create_date,
+            last_modify_date: fixture.meta.last_modify_date,
+        },
+        channels,
+        modes,
+    };
+    definition.validate()?;
+    Ok(definition)
+}
+
+/// Loads an OFL fixture file, taking the manufacturer from its directory name.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<FixtureDefinition, Box<dyn Error>> {
+    let path = path.as_ref();
+    let manufacturer = path
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    parse(&fs::read_to_string(path)?, &manufacturer)
+}
+
+// `defaultValue` is either a DMX value or a percentage string such as "50%".
+fn parse_default_value(value: Option<&Value>) -> Result<u8, Box<dyn Error>> {
+    match value {
+        None => Ok(0),
+        Some(Value::Number(n)) => n
+            .as_u64()
+            .filter(|&n| n <= 255)
+            .map(|n| n as u8)
+            .ok_or_else(|| "defaultValue must be between 0 and 255".into()),
+        Some(Value::String(s)) => {
+            let percent: f32 = s.trim_end_matches('%').trim().parse()?;
+            Ok((percent.clamp(0.0, 100.0) / 100.0 * 255.0).round() as u8)
+        }
+        Some(_) => Err("defaultValue must be a number or percentage".into()),
+    }
+}
+
+fn parse_capabilities(
+    channel: &str,
+    single: Option<OflCapability>,
+    list: Option<Vec<OflCapability>>,
+) -> Result<Vec<Capability>, Box<dyn Error>> {
+    let list = match (single, list) {
+        (Some(cap), None) => vec![cap],
+        (None, Some(list)) => list,
+        _ => return Err(format!("channel '{}' needs exactly one of capability or capabilities", channel).into()),
+    };
+
+    let mut capabilities = Vec::new();
+    for cap in list {
+        let [min, max] = cap.dmx_range.unwrap_or([0, 255]);
+        let (Ok(min), Ok(max)) = (u8::try_from(min), u8::try_from(max)) else {
+            return Err(format!("channel '{}': only 8-bit DMX ranges are supported", channel).into());
+        };
+        if min > max {
+            return Err(format!("channel '{}': dmxRange [{}, {}] runs backwards", channel, min, max).into());
+        }
+        let mut properties = cap.properties;
+        let name = match (cap.comment, cap.effect_name) {
+            (Some(comment), Some(effect_name)) => {
+                properties.insert("effectName".to_string(), Value::String(effect_name));
+                comment
+            }
+            (comment, effect_name) => comment.or(effect_name).unwrap_or_else(|| cap.kind.clone()),
+        };
+        capabilities.push(Capability { min, max, kind: cap.kind, name, properties });
+    }
+    Ok(capabilities)
+}
+
+/// Writes `definition` as an OFL fixture file. The manufacturer is not part of
+/// the file; save it as `<manufacturer>/<fixture>.json` so [`load`] finds it.
+///
+/// The meta block is the definition's own. `date` (YYYY-MM-DD) stands in for
+/// dates it doesn't have, as with definitions read from QLC+ files; an empty
+/// author list is credited to laserport.
+pub fn export(definition: &FixtureDefinition, date: &str) -> String {
+    let mut fixture = Map::new();
+    fixture.insert("$schema".to_string(), json!(SCHEMA_URL));
+    fixture.insert("name".to_string(), json!(definition.name));
+    fixture.insert("categories".to_string(), json!(definition.categories));
+    fixture.insert("meta".to_string(), export_meta(&definition.meta, date));
+
+    let physical = export_physical(&definition.physical);
+    if !physical.is_empty() {
+        fixture.insert("physical".to_string(), Value::Object(physical));
+    }
+
+    // Fine channels are listed on their coarse channel rather than on their own
+    let fine: Vec<&String> = definition.channels.iter().flat_map(|channel| &channel.fine_channel_aliases).collect();
+    let mut channels = Map::new();
+    for channel in definition.channels.iter().filter(|channel| !fine.contains(&&channel.name)) {
+        let mut entry = Map::new();
+        if channel.default_value != 0 {
+            entry.insert("defaultValue".to_string(), json!(channel.default_value));
+        }
+        if !channel.fine_channel_aliases.is_empty() {
+            entry.insert("fineChannelAliases".to_string(), json!(channel.fine_channel_aliases));
+        }
+        match channel.capabilities.as_slice() {
+            [only] if only.min == 0 && only.max == 255 => {
+                entry.insert("capability".to_string(), export_capability(only, false));
+            }
+            capabilities => {
+                let list = capabilities.iter().map(|cap| export_capability(cap, true)).collect();
+                entry.insert("capabilities".to_string(), Value::Array(list));
+            }
+        }
+        channels.insert(channel.name.clone(), Value::Object(entry));
+    }
+    fixture.insert("availableChannels".to_string(), Value::Object(channels));
+
+    let modes: Vec<Value> = definition
+        .modes
+        .iter()
+        .map(|mode| {
+            let mut entry = Map::new();
+            entry.insert("name".to_string(), json!(mode.name));
+            if let Some(short_name) = &mode.short_name {
+                entry.insert("shortName".to_string(), json!(short_name));
+            }
+            entry.insert("channels".to_string(), json!(mode.channels));
+            Value::Object(entry)
+        })
+        .collect();
+    fixture.insert("modes".to_string(), Value::Array(modes));
+
+    let mut text = serde_json::to_string_pretty(&Value::Object(fixture)).unwrap_or_default();
+    text.push('\n');
+    text
+}
+
+/// Writes `definition` to `path` as an OFL fixture file; see [`export`] for `date`.
+pub fn save<P: AsRef<Path>>(definition: &FixtureDefinition, date: &str, path: P) -> Result<(), Box<dyn Error>> {
+    fs::write(path, export(definition, date))?;
+    Ok(())
+}
+
+fn export_physical(physical: &Physical) -> Map<String, Value> {
+    let mut entry = Map::new();
+    if let Some(dimensions) = physical.dimensions_mm {
+        entry.insert("dimensions".to_string(), json!(dimensions));
+    }
+    if let Some(weight) = physical.weight_kg {
+        entry.insert("weight".to_string(), json!(weight));
+    }
+    if let Some(power) = physical.power_w {
+        entry.insert("power".to_string(), json!(power));
+    }
+    if let Some(connector) = &physical.dmx_connector {
+        entry.insert("DMXconnector".to_string(), json!(connector));
+    }
+    if let Some(bulb) = &physical.bulb {
+        entry.insert("bulb".to_string(), json!({ "type": bulb }));
+    }
+    entry
+}
+
+fn export_meta(meta: &Meta, date: &str) -> Value {
+    let authors = if meta.authors.is_empty() { vec!["laserport".to_string()] } else { meta.authors.clone() };
+    let create_date = meta.create_date.as_deref().unwrap_or(date);
+    let last_modify_date = meta.last_modify_date.as_deref().unwrap_or(create_date);
+    json!({ "authors": authors, "createDate": create_date, "lastModifyDate": last_modify_date })
+}
+
+// OFL requires `effectName` or `effectPreset` on Effect capabilities; everything
+// else carries the name as a comment unless it is just the type.
+fn export_capability(cap: &Capability, with_range: bool) -> Value {
+    let mut entry = Map::new();
+    if with_range {
+        entry.insert("dmxRange".to_string(), json!([cap.min, cap.max]));
+    }
+    entry.insert("type".to_string(), json!(cap.kind));
+    for (key, value) in &cap.properties {
+        entry.insert(key.clone(), value.clone());
+    }
+    let needs_effect_name =
+        cap.kind == "Effect" && !cap.properties.contains_key("effectName") && !cap.properties.contains_key("effectPreset");
+    if needs_effect_name {
+        entry.insert("effectName".to_string(), json!(cap.name));
+    } else if cap.name != cap.kind {
+        entry.insert("comment".to_string(), json!(cap.name));
+    }
+    Value::Object(entry)
+}
@@ -0,0 +1,424 @@
+//! QLC+ fixture definition files (`.qxf`).
+
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use super::definition::{Capability, ChannelDefinition, FixtureDefinition, Meta, ModeDefinition, Physical};
+
+// Just enough of a DOM to walk a fixture file; QLC+ files are small.
+#[derive(Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|child| child.text.as_str())
+    }
+
+    fn number<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, Box<dyn Error>> {
+        match self.attribute(name) {
+            None => Ok(None),
+            Some(value) => value
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("<{}> attribute {}='{}' is not a number", self.name, name, value).into()),
+        }
+    }
+}
+
+fn start_element(start: &BytesStart) -> Result<Element, Box<dyn Error>> {
+    let mut element = Element {
+        name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+        ..Element::default()
+    };
+    for attribute in start.attributes() {
+        let attribute = attribute?;
+        let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned();
+        element.attributes.push((key, attribute.unescape_value()?.into_owned()));
+    }
+    Ok(element)
+}
+
+fn parse_document(xml: &str) -> Result<Element, Box<dyn Error>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut stack: Vec<Element> = Vec::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => stack.push(start_element(&start)?),
+            Event::Empty(start) => {
+                let element = start_element(&start)?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element),
+                }
+            }
+            Event::Text(text) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text.unescape()?);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&String::from_utf8_lossy(&data));
+                }
+            }
+            Event::End(_) => {
+                let element = stack.pop().ok_or("unbalanced XML")?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element),
+                }
+            }
+            Event::Eof => return Err("unexpected end of XML".into()),
+            _ => {}
+        }
+    }
+}
+
+/// Capability type (Open Fixture Library vocabulary) for a QLC+ channel group.
+/// QLC+ types whole channels rather than capabilities, so every capability on
+/// the channel gets the same kind.
+pub fn kind_for_group(group: &str) -> &'static str {
+    match group {
+        "Intensity" => "Intensity",
+        "Colour" => "ColorPreset",
+        "Gobo" => "WheelSlot",
+        "Prism" => "Prism",
+        "Shutter" => "ShutterStrobe",
+        "Beam" => "Zoom",
+        "Speed" => "Speed",
+        "Effect" => "Effect",
+        "Pan" => "Pan",
+        "Tilt" => "Tilt",
+        "Maintenance" => "Maintenance",
+        "Nothing" => "NoFunction",
+        _ => "Generic",
+    }
+}
+
+/// QLC+ channel group for a capability type; the inverse of [`kind_for_group`].
+pub fn group_for_kind(kind: &str) -> &'static str {
+    match kind {
+        "Intensity" => "Intensity",
+        "ColorPreset" | "ColorIntensity" | "ColorTemperature" => "Colour",
+        "WheelSlot" | "WheelShake" | "WheelSlotRotation" | "WheelRotation" => "Gobo",
+        "Prism" | "PrismRotation" => "Prism",
+        "ShutterStrobe" | "StrobeSpeed" | "StrobeDuration" => "Shutter",
+        "Zoom" | "Focus" | "Iris" | "Frost" | "BeamAngle" | "BeamPosition" => "Beam",
+        "Speed" | "EffectSpeed" | "EffectDuration" | "PanTiltSpeed" => "Speed",
+        "Pan" | "PanContinuous" => "Pan",
+        "Tilt" | "TiltContinuous" => "Tilt",
+        "Maintenance" => "Maintenance",
+        "NoFunction" => "Nothing",
+        _ => "Effect",
+    }
+}
+
+// QLC+ 4.12 replaced <Group> on common channels with a <Preset> such as
+// "IntensityDimmer" or "PositionPan"; map those back to a group.
+fn group_for_preset(preset: &str) -> &'static str {
+    const PREFIXES: [(&str, &str); 9] = [
+        ("Intensity", "Intensity"),
+        ("PositionPan", "Pan"),
+        ("PositionTilt", "Tilt"),
+        ("Color", "Colour"),
+        ("Gobo", "Gobo"),
+        ("Prism", "Prism"),
+        ("Shutter", "Shutter"),
+        ("Beam", "Beam"),
+        ("Speed", "Speed"),
+    ];
+    PREFIXES
+        .iter()
+        .find(|(prefix, _)| preset.starts_with(prefix))
+        .map(|(_, group)| *group)
+        .unwrap_or("Effect")
+}
+
+fn parse_physical(physical: Option<&Element>) -> Result<Physical, Box<dyn Error>> {
+    let Some(physical) = physical else {
+        return Ok(Physical::default());
+    };
+    let mut result = Physical::default();
+    if let Some(bulb) = physical.child("Bulb") {
+        result.bulb = bulb.attribute("Type").filter(|kind| !kind.is_empty()).map(str::to_string);
+    }
+    if let Some(dimensions) = physical.child("Dimensions") {
+        result.weight_kg = dimensions.number("Weight")?.filter(|&weight: &f32| weight > 0.0);
+        let size: [Option<f32>; 3] = [dimensions.number("Width")?, dimensions.number("Height")?, dimensions.number("Depth")?];
+        if let [Some(width), Some(height), Some(depth)] = size
+            && (width, height, depth) != (0.0, 0.0, 0.0)
+        {
+            result.dimensions_mm = Some([width, height, depth]);
+        }
+    }
+    if let Some(technical) = physical.child("Technical") {
+        result.power_w = technical.number("PowerConsumption")?.filter(|&power: &f32| power > 0.0);
+        result.dmx_connector = technical.attribute("DmxConnector").filter(|c| !c.is_empty()).map(str::to_string);
+    }
+    Ok(result)
+}
+
+// A channel as read, with what's needed to find the coarse channel of a fine one.
+struct ParsedChannel {
+    definition: ChannelDefinition,
+    group: String,
+    fine: bool,
+}
+
+fn parse_channel(channel: &Element) -> Result<ParsedChannel, Box<dyn Error>> {
+    let name = channel.attribute("Name").ok_or("<Channel> without a Name")?.to_string();
+    let preset = channel.attribute("Preset").or(channel.child_text("Preset"));
+    let group = match (channel.child_text("Group"), preset) {
+        (Some(group), _) => group,
+        (None, Some(preset)) => group_for_preset(preset),
+        (None, None) => "",
+    };
+    // Byte 1 is the fine (LSB) half of a 16-bit value, as are presets like "PositionPanFine"
+    let fine = match channel.child("Group") {
+        Some(group) => group.number::<u8>("Byte")?.unwrap_or(0) > 0,
+        None => preset.is_some_and(|preset| preset.ends_with("Fine")),
+    };
+    // Like OFL fine channel aliases, a fine channel has no function of its own
+    let kind = if fine { "Generic" } else { kind_for_group(group) };
+
+    let mut capabilities = Vec::new();
+    for capability in channel.children("Capability") {
+        let range = (capability.number::<u16>("Min")?, capability.number::<u16>("Max")?);
+        let (Some(min), Some(max)) = range else {
+            return Err(format!("channel '{}': capability without Min and Max", name).into());
+        };
+        let (Ok(min), Ok(max)) = (u8::try_from(min), u8::try_from(max)) else {
+            return Err(format!("channel '{}': capability range above 255", name).into());
+        };
+        if min > max {
+            return Err(format!("channel '{}': capability {}-{} runs backwards", name, min, max).into());
+        }
+        let label = capability.text.trim();
+        capabilities.push(Capability::new(min, max, kind, if label.is_empty() { kind } else { label }));
+    }
+    // Channels without capabilities (plain dimmers) are one 0-255 range.
+    if capabilities.is_empty() {
+        capabilities.push(Capability::new(0, 255, kind, if fine { "Fine" } else { &name }));
+    }
+
+    let default_value = channel
+        .number::<u16>("Default")?
+        .map(|value| u8::try_from(value).map_err(|_| format!("channel '{}': Default above 255", name)))
+        .transpose()?
+        .unwrap_or(0);
+    Ok(ParsedChannel {
+        definition: ChannelDefinition { name, default_value, capabilities, fine_channel_aliases: Vec::new() },
+        group: group.to_string(),
+        fine,
+    })
+}
+
+// QLC+ doesn't say which coarse channel a fine one belongs to: take the nearest
+// coarse channel of the same group before it, or failing that after it.
+fn link_fine_channels(parsed: Vec<ParsedChannel>) -> Result<Vec<ChannelDefinition>, Box<dyn Error>> {
+    let mut aliases: Vec<Vec<String>> = vec![Vec::new(); parsed.len()];
+    for (index, channel) in parsed.iter().enumerate().filter(|(_, channel)| channel.fine) {
+        let is_coarse = |other: &ParsedChannel| !other.fine && other.group == channel.group;
+        let coarse = parsed[..index]
+            .iter()
+            .rposition(is_coarse)
+            .or_else(|| parsed[index + 1..].iter().position(is_coarse).map(|offset| index + 1 + offset))
+            .ok_or_else(|| {
+                format!("fine channel '{}' has no {} channel to belong to", channel.definition.name, channel.group)
+            })?;
+        aliases[coarse].push(channel.definition.name.clone());
+    }
+    Ok(parsed
+        .into_iter()
+        .zip(aliases)
+        .map(|(channel, fine_channel_aliases)| ChannelDefinition { fine_channel_aliases, ..channel.definition })
+        .collect())
+}
+
+fn parse_mode(mode: &Element) -> Result<ModeDefinition, Box<dyn Error>> {
+    let name = mode.attribute("Name").ok_or("<Mode> without a Name")?.to_string();
+    let mut numbered = Vec::new();
+    for channel in mode.children("Channel") {
+        let number: usize = channel
+            .number("Number")?
+            .ok_or_else(|| format!("mode '{}': channel without a Number", name))?;
+        numbered.push((number, channel.text.trim().to_string()));
+    }
+    numbered.sort_by_key(|(number, _)| *number);
+    for (index, (number, _)) in numbered.iter().enumerate() {
+        if *number != index {
+            return Err(format!("mode '{}': channel numbers must run 0..{}", name, numbered.len()).into());
+        }
+    }
+    Ok(ModeDefinition {
+        name,
+        short_name: None,
+        channels: numbered.into_iter().map(|(_, channel)| channel).collect(),
+    })
+}
+
+/// Parses a QLC+ fixture definition.
+///
+/// Channel groups become capability kinds (see [`kind_for_group`]); fine
+/// (`Byte="1"`) channels become fine channel aliases of the coarse channel of
+/// their group. Per-mode physical data is ignored unless the fixture has none of its own.
+pub fn parse(xml: &str) -> Result<FixtureDefinition, Box<dyn Error>> {
+    let root = parse_document(xml)?;
+    if root.name != "FixtureDefinition" {
+        return Err(format!("expected <FixtureDefinition>, found <{}>", root.name).into());
+    }
+
+    let channels = link_fine_channels(root.children("Channel").map(parse_channel).collect::<Result<Vec<_>, _>>()?)?;
+    let modes = root.children("Mode").map(parse_mode).collect::<Result<Vec<_>, _>>()?;
+    let physical = root
+        .child("Physical")
+        .or_else(|| root.children("Mode").find_map(|mode| mode.child("Physical")));
+
+    let definition = FixtureDefinition {
+        manufacturer: root.child_text("Manufacturer").unwrap_or_default().to_string(),
+        name: root.child_text("Model").ok_or("fixture has no <Model>")?.to_string(),
+        categories: root.child_text("Type").map(|kind| vec![kind.to_string()]).unwrap_or_default(),
+        physical: parse_physical(physical)?,
+        meta: Meta {
+            authors: root
+                .child("Creator")
+                .and_then(|creator| creator.child_text("Author"))
+                .filter(|author| !author.is_empty())
+                .map(|author| vec![author.to_string()])
+                .unwrap_or_default(),
+            ..Meta::default()
+        },
+        channels,
+        modes,
+    };
+    definition.validate()?;
+    Ok(definition)
+}
+
+/// Loads a `.qxf` file.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<FixtureDefinition, Box<dyn Error>> {
+    parse(&fs::read_to_string(path)?)
+}
+
+// A channel takes the group of its first capability that does something.
+fn channel_group(channel: &ChannelDefinition) -> &'static str {
+    let mut kinds = channel.capabilities.iter().map(|cap| cap.kind.as_str());
+    if kinds.clone().all(|kind| kind == "NoFunction") {
+        return "Nothing";
+    }
+    kinds
+        .find(|&kind| kind != "NoFunction" && kind != "Generic")
+        .map(group_for_kind)
+        .unwrap_or("Effect")
+}
+
+/// Writes `definition` as a QLC+ fixture definition.
+///
+/// QLC+ types channels rather than capabilities, so capability kinds collapse
+/// to the channel's group (see [`group_for_kind`]); fine channels are written
+/// as byte 1 of their coarse channel's group. Mode short names, extra
+/// categories, meta dates and OFL-only capability properties have no place in
+/// the format and are dropped.
+pub fn export(definition: &FixtureDefinition) -> String {
+    let mut xml = String::new();
+    let _ = write_definition(&mut xml, definition);
+    xml
+}
+
+/// Writes `definition` to `path` as a `.qxf` file.
+pub fn save<P: AsRef<Path>>(definition: &FixtureDefinition, path: P) -> Result<(), Box<dyn Error>> {
+    fs::write(path, export(definition))?;
+    Ok(())
+}
+
+fn write_definition(xml: &mut String, definition: &FixtureDefinition) -> std::fmt::Result {
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(xml, "<!DOCTYPE FixtureDefinition>")?;
+    writeln!(xml, r#"<FixtureDefinition xmlns="http://www.qlcplus.org/FixtureDefinition">"#)?;
+    writeln!(xml, " <Creator>")?;
+    writeln!(xml, "  <Name>laserport</Name>")?;
+    writeln!(xml, "  <Version>{}</Version>", env!("CARGO_PKG_VERSION"))?;
+    let author = if definition.meta.authors.is_empty() { "laserport".to_string() } else { definition.meta.authors.join(", ") };
+    writeln!(xml, "  <Author>{}</Author>", escape(&author))?;
+    writeln!(xml, " </Creator>")?;
+    writeln!(xml, " <Manufacturer>{}</Manufacturer>", escape(&definition.manufacturer))?;
+    writeln!(xml, " <Model>{}</Model>", escape(&definition.name))?;
+    let kind = definition.categories.first().map(String::as_str).unwrap_or("Other");
+    writeln!(xml, " <Type>{}</Type>", escape(kind))?;
+
+    for channel in &definition.channels {
+        // Fine channels go in their coarse channel's group as byte 1
+        let coarse = definition.channels.iter().find(|coarse| coarse.fine_channel_aliases.contains(&channel.name));
+        let (group, byte) = match coarse {
+            Some(coarse) => (channel_group(coarse), 1),
+            None => (channel_group(channel), 0),
+        };
+        writeln!(xml, r#" <Channel Name="{}" Default="{}">"#, escape(&channel.name), channel.default_value)?;
+        writeln!(xml, r#"  <Group Byte="{}">{}</Group>"#, byte, group)?;
+        for cap in &channel.capabilities {
+            writeln!(xml, r#"  <Capability Min="{}" Max="{}">{}</Capability>"#, cap.min, cap.max, escape(&cap.name))?;
+        }
+        writeln!(xml, " </Channel>")?;
+    }
+
+    for mode in &definition.modes {
+        writeln!(xml, r#" <Mode Name="{}">"#, escape(&mode.name))?;
+        for (number, channel) in mode.channels.iter().enumerate() {
+            writeln!(xml, r#"  <Channel Number="{}">{}</Channel>"#, number, escape(channel))?;
+        }
+        writeln!(xml, " </Mode>")?;
+    }
+
+    // QLC+ expects every element; zero means unknown.
+    let physical = &definition.physical;
+    let [width, height, depth] = physical.dimensions_mm.unwrap_or([0.0; 3]);
+    writeln!(xml, " <Physical>")?;
+    writeln!(
+        xml,
+        r#"  <Bulb Type="{}" Lumens="0" ColourTemperature="0"/>"#,
+        escape(physical.bulb.as_deref().unwrap_or_default())
+    )?;
+    writeln!(
+        xml,
+        r#"  <Dimensions Weight="{}" Width="{}" Height="{}" Depth="{}"/>"#,
+        physical.weight_kg.unwrap_or(0.0),
+        width,
+        height,
+        depth
+    )?;
+    writeln!(xml, r#"  <Lens Name="Other" DegreesMin="0" DegreesMax="0"/>"#)?;
+    writeln!(xml, r#"  <Focus Type="Fixed" PanMax="0" TiltMax="0"/>"#)?;
+    writeln!(
+        xml,
+        r#"  <Technical PowerConsumption="{}" DmxConnector="{}"/>"#,
+        physical.power_w.unwrap_or(0.0),
+        escape(physical.dmx_connector.as_deref().unwrap_or_default())
+    )?;
+    writeln!(xml, " </Physical>")?;
+    writeln!(xml, "</FixtureDefinition>")
+}
@@ -1,2 +1,6 @@
 pub mod dmx;
-pub mod dmxcharts;
\ No newline at end of file
+pub mod dmxcharts;
+pub mod fixture;
+pub mod patch;
+pub mod playback;
+pub mod transport;
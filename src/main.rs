@@ -1,3 +1,14 @@
-fn main() {
-    println!("laserport");
+mod cli;
+
+use clap::Parser;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match cli::run(cli::Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("laserport: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
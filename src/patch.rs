@@ -0,0 +1,220 @@
+use std::error::Error;
+
+use crate::dmx::{DmxState, DMX_FRAME_SIZE};
+use crate::fixture::Fixture;
+
+/// Index of a fixture in a [`Patch`], in the order it was added.
+pub type FixtureId = usize;
+
+/// A fixture placed at a DMX start address.
+pub struct PatchedFixture {
+    name: String,
+    /// Start address, 1-based.
+    address: usize,
+    /// Channels taken in the universe, fixed when patched.
+    footprint: usize,
+    fixture: Box<dyn Fixture>,
+}
+
+impl PatchedFixture {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Start address, 1-based.
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    pub fn fixture(&self) -> &dyn Fixture {
+        self.fixture.as_ref()
+    }
+
+    /// Last channel the fixture occupies, 1-based.
+    pub fn last_channel(&self) -> usize {
+        self.address + self.footprint - 1
+    }
+}
+
+/// Named fixtures that effects treat as one row, e.g. four lasers left to right.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixtureGroup {
+    pub name: String,
+    /// Members in stage order; effects spread across them in this order.
+    pub members: Vec<FixtureId>,
+}
+
+/// Several fixtures sharing one universe.
+#[derive(Default)]
+pub struct Patch {
+    fixtures: Vec<PatchedFixture>,
+    groups: Vec<FixtureGroup>,
+}
+
+impl Patch {
+    pub fn new() -> Self {
+        Patch { fixtures: Vec::new(), groups: Vec::new() }
+    }
+
+    /// Patches `fixture` at `address` (1-based). Fails if the name is taken, or if it
+    /// would run past channel 512 or share a channel with an already patched fixture.
+    pub fn add(&mut self, name: &str, address: usize, fixture: Box<dyn Fixture>) -> Result<FixtureId, Box<dyn Error>> {
+        if self.find(name).is_some() {
+            return Err(format!("there is already a fixture '{}'", name).into());
+        }
+        let footprint = fixture.footprint();
+        if footprint == 0 {
+            return Err(format!("fixture '{}' has no channels", name).into());
+        }
+        if address < 1 || footprint > DMX_FRAME_SIZE || address > DMX_FRAME_SIZE + 1 - footprint {
+            return Err(format!(
+                "fixture '{}' at address {} with {} channels does not fit in the universe",
+                name, address, footprint
+            )
+            .into());
+        }
+        if let Some(other) = self.overlapping(address, footprint) {
+            let other = &self.fixtures[other];
+            return Err(format!(
+                "fixture '{}' at {}-{} overlaps '{}' at {}-{}",
+                name,
+                address,
+                address + footprint - 1,
+                other.name,
+                other.address,
+                other.last_channel()
+            )
+            .into());
+        }
+
+        self.fixtures.push(PatchedFixture { name: name.to_string(), address, footprint, fixture });
+        Ok(self.fixtures.len() - 1)
+    }
+
+    /// First patched fixture sharing any channel with `address..address + footprint`.
+    /// Nothing overlaps an empty range.
+    pub fn overlapping(&self, address: usize, footprint: usize) -> Option<FixtureId> {
+        if footprint == 0 {
+            return None;
+        }
+        let last = address.saturating_add(footprint - 1);
+        self.fixtures
+            .iter()
+            .position(|patched| address <= patched.last_channel() && patched.address <= last)
+    }
+
+    pub fn get(&self, id: FixtureId) -> Option<&PatchedFixture> {
+        self.fixtures.get(id)
+    }
+
+    /// The fixture patched as `id`, to change its values. Its address and name stay put.
+    pub fn fixture_mut(&mut self, id: FixtureId) -> Option<&mut dyn Fixture> {
+        Some(self.fixtures.get_mut(id)?.fixture.as_mut())
+    }
+
+    pub fn find(&self, name: &str) -> Option<FixtureId> {
+        self.fixtures.iter().position(|patched| patched.name == name)
+    }
+
+    pub fn fixtures(&self) -> &[PatchedFixture] {
+        &self.fixtures
+    }
+
+    /// Universe channel (1-based) of a fixture's channel, e.g. ("laser1", "Pattern size").
+    pub fn channel(&self, fixture: &str, channel: &str) -> Option<usize> {
+        self.channel_of(self.find(fixture)?, channel)
+    }
+
+    fn channel_of(&self, id: FixtureId, channel: &str) -> Option<usize> {
+        let patched = self.fixtures.get(id)?;
+        let offset = patched.fixture.channel_names().iter().position(|name| name == channel)?;
+        Some(patched.address + offset)
+    }
+
+    /// Groups `members` (patched fixture names, in stage order) as `name`.
+    pub fn add_group(&mut self, name: &str, members: &[&str]) -> Result<(), Box<dyn Error>> {
+        if self.group(name).is_some() {
+            return Err(format!("there is already a group '{}'", name).into());
+        }
+        if members.is_empty() {
+            return Err(format!("group '{}' has no members", name).into());
+        }
+        let mut ids = Vec::new();
+        for member in members {
+            let id = self.find(member).ok_or_else(|| format!("group '{}': no fixture '{}'", name, member))?;
+            if ids.contains(&id) {
+                return Err(format!("group '{}' lists '{}' twice", name, member).into());
+            }
+            ids.push(id);
+        }
+        self.groups.push(FixtureGroup { name: name.to_string(), members: ids });
+        Ok(())
+    }
+
+    pub fn group(&self, name: &str) -> Option<&FixtureGroup> {
+        self.groups.iter().find(|group| group.name == name)
+    }
+
+    pub fn groups(&self) -> &[FixtureGroup] {
+        &self.groups
+    }
+
+    /// Universe channel of `channel` on each member of `group`, in member order.
+    pub fn group_channels(&self, group: &str, channel: &str) -> Result<Vec<usize>, Box<dyn Error>> {
+        let group = self.group(group).ok_or_else(|| format!("no group '{}'", group))?;
+        group
+            .members
+            .iter()
+            .map(|&id| {
+                self.channel_of(id, channel)
+                    .ok_or_else(|| format!("fixture '{}' has no channel '{}'", self.fixtures[id].name, channel).into())
+            })
+            .collect()
+    }
+
+    /// Fixture and channel name (e.g. "laser1 Pattern size") of a 1-based universe channel.
+    pub fn channel_name(&self, channel: usize) -> Option<String> {
+        let patched = self
+            .fixtures
+            .iter()
+            .find(|patched| patched.address <= channel && channel <= patched.last_channel())?;
+        let names = patched.fixture.channel_names();
+        let name = names.get(channel - patched.address)?;
+        Some(format!("{} {}", patched.name, name))
+    }
+
+    /// Renders every fixture into one 512-channel universe, for a controller at address 1.
+    /// A fixture whose footprint changed since it was patched only fills the channels it was patched on.
+    pub fn render(&self) -> DmxState {
+        let mut universe = DmxState::new(DMX_FRAME_SIZE);
+        for patched in &self.fixtures {
+            let start = patched.address - 1;
+            let slots = &mut universe.channels[start..start + patched.footprint];
+            if patched.fixture.footprint() == patched.footprint {
+                patched.fixture.encode(slots);
+            } else {
+                let mut values = vec![0u8; patched.fixture.footprint()];
+                patched.fixture.encode(&mut values);
+                let len = values.len().min(slots.len());
+                slots[..len].copy_from_slice(&values[..len]);
+            }
+        }
+        universe
+    }
+
+    /// Updates every fixture from a universe, e.g. one captured from a console.
+    pub fn decode(&mut self, universe: &DmxState) -> Result<(), Box<dyn Error>> {
+        for patched in &mut self.fixtures {
+            let start = patched.address - 1;
+            if patched.fixture.footprint() != patched.footprint {
+                return Err(format!("fixture '{}' no longer has the {} channels it was patched with", patched.name, patched.footprint).into());
+            }
+            let slots = universe
+                .channels
+                .get(start..start + patched.footprint)
+                .ok_or_else(|| format!("universe is too short for fixture '{}'", patched.name))?;
+            patched.fixture.decode(slots)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,382 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{fixture_values, FadeProfile};
+use crate::dmx::{DmxState, FrameSource};
+use crate::fixture::Fixture;
+
+/// Taps further apart than this start a new tempo instead of refining the old one.
+pub const TAP_RESET: Duration = Duration::from_secs(2);
+/// How many recent taps are averaged into the tempo.
+const TAP_HISTORY: usize = 5;
+/// Shortest step, so a chase of zero-length steps can't spin.
+const MIN_STEP: Duration = Duration::from_millis(1);
+
+/// One look in a [`Chase`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Step {
+    /// 1-based universe channel to value.
+    pub values: BTreeMap<usize, u8>,
+    /// Crossfade from the previous step; used with [`ChaseTiming::Steps`].
+    pub fade: Duration,
+    /// Time on this look after the fade; used with [`ChaseTiming::Steps`].
+    pub hold: Duration,
+}
+
+impl Step {
+    pub fn new(hold: Duration) -> Self {
+        Step { values: BTreeMap::new(), fade: Duration::ZERO, hold }
+    }
+
+    pub fn with_fade(self, fade: Duration) -> Self {
+        Step { fade, ..self }
+    }
+
+    pub fn with_channel(mut self, channel: usize, value: u8) -> Self {
+        self.values.insert(channel, value);
+        self
+    }
+
+    /// Stores every channel of `fixture` (a `LaserState`, say) as patched at `address`.
+    pub fn with_fixture(mut self, address: usize, fixture: &dyn Fixture) -> Result<Self, Box<dyn Error>> {
+        self.values.extend(fixture_values(address, fixture)?);
+        Ok(self)
+    }
+}
+
+/// Order a [`Chase`] runs its steps in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Forward,
+    Reverse,
+    /// Forward then back again, without repeating the end steps.
+    Bounce,
+    /// Any step but the current one.
+    Random,
+}
+
+/// Where a [`Chase`] takes its step times from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChaseTiming {
+    /// Each step's own fade and hold.
+    Steps,
+    /// Every step lasts `beats` beats at `bpm`, crossfading for `fade` (0.0-1.0) of it.
+    Tempo { bpm: f32, beats: f32, fade: f32 },
+}
+
+impl ChaseTiming {
+    /// Whole step length and crossfade length for `step`.
+    fn times(&self, step: &Step) -> (Duration, Duration) {
+        match *self {
+            ChaseTiming::Steps => (step.fade.saturating_add(step.hold).max(MIN_STEP), step.fade),
+            ChaseTiming::Tempo { bpm, beats, fade } => {
+                // The fields are public, so anything can turn up: a step too long to
+                // represent lasts for good, and NaN (infinite beats at infinite bpm) is no time
+                let seconds = 60.0 / bpm.max(1.0) * beats.max(0.0);
+                let length = match Duration::try_from_secs_f32(seconds) {
+                    Ok(length) => length.max(MIN_STEP),
+                    Err(_) if seconds > 0.0 => Duration::MAX,
+                    Err(_) => MIN_STEP,
+                };
+                let fade = if fade.is_nan() { 0.0 } else { fade.clamp(0.0, 1.0) };
+                (length, Duration::try_from_secs_f32(length.as_secs_f32() * fade).unwrap_or(length))
+            }
+        }
+    }
+}
+
+enum Command {
+    Start,
+    Stop,
+}
+
+struct Run {
+    step: usize,
+    started: Instant,
+    /// What the step fades from: the previous step's values, or the output when the chase started.
+    from: BTreeMap<usize, u8>,
+    loops_done: u32,
+    steps_taken: usize,
+    bouncing_back: bool,
+    finished: bool,
+}
+
+struct ChaseState {
+    steps: Vec<Step>,
+    direction: Direction,
+    loops: Option<u32>,
+    timing: ChaseTiming,
+    profile: FadeProfile,
+    rng: StdRng,
+    taps: Vec<Instant>,
+    pending: Vec<Command>,
+    run: Option<Run>,
+    output: BTreeMap<usize, u8>,
+}
+
+impl ChaseState {
+    fn set_bpm(&mut self, bpm: f32) {
+        self.timing = match self.timing {
+            ChaseTiming::Tempo { beats, fade, .. } => ChaseTiming::Tempo { bpm, beats, fade },
+            ChaseTiming::Steps => ChaseTiming::Tempo { bpm, beats: 1.0, fade: 0.0 },
+        };
+    }
+
+    fn first_step(&mut self) -> usize {
+        match self.direction {
+            Direction::Forward | Direction::Bounce => 0,
+            Direction::Reverse => self.steps.len() - 1,
+            Direction::Random => self.rng.random_range(0..self.steps.len()),
+        }
+    }
+
+    /// Moves `run` to the step after its current one, or marks it finished when the loops run out.
+    fn advance(&mut self, due: Instant) {
+        let count = self.steps.len();
+        let Some(run) = self.run.as_mut() else {
+            return;
+        };
+        let current = run.step;
+        run.steps_taken += 1;
+        // The next step within this pass, or None when the pass is over
+        let within_pass = match self.direction {
+            Direction::Forward => (current + 1 < count).then_some(current + 1),
+            Direction::Reverse => current.checked_sub(1),
+            Direction::Bounce if count < 2 => None,
+            Direction::Bounce if !run.bouncing_back => {
+                if current + 1 < count {
+                    Some(current + 1)
+                } else {
+                    run.bouncing_back = true;
+                    Some(current - 1)
+                }
+            }
+            Direction::Bounce => {
+                run.bouncing_back = current > 0;
+                current.checked_sub(1)
+            }
+            Direction::Random => {
+                (run.steps_taken % count != 0).then(|| random_other(&mut self.rng, current, count))
+            }
+        };
+        let next = match within_pass {
+            Some(next) => next,
+            None => {
+                run.loops_done += 1;
+                if self.loops.is_some_and(|loops| run.loops_done >= loops) {
+                    run.finished = true;
+                    return;
+                }
+                match self.direction {
+                    Direction::Forward => 0,
+                    Direction::Reverse => count - 1,
+                    Direction::Bounce => 1.min(count - 1),
+                    Direction::Random => random_other(&mut self.rng, current, count),
+                }
+            }
+        };
+        run.from = self.steps[current].values.clone();
+        run.step = next;
+        run.started = due;
+    }
+
+    fn values_at(&self, at: Instant, base: &DmxState) -> BTreeMap<usize, u8> {
+        let Some(run) = &self.run else {
+            return BTreeMap::new();
+        };
+        let step = &self.steps[run.step];
+        let (_, fade) = self.timing.times(step);
+        let progress = match at.saturating_duration_since(run.started) {
+            _ if run.finished || fade.is_zero() => 1.0,
+            elapsed => (elapsed.as_secs_f32() / fade.as_secs_f32()).min(1.0),
+        };
+        let mut values = BTreeMap::new();
+        for &channel in step.values.keys().chain(run.from.keys()) {
+            let base_value = base.get_channel(channel).unwrap_or(0);
+            let from = run.from.get(&channel).copied().unwrap_or(base_value);
+            let to = step.values.get(&channel).copied().unwrap_or(base_value);
+            if !step.values.contains_key(&channel) && progress >= 1.0 {
+                continue; // Only the previous step used it and it's back at the base
+            }
+            values.insert(channel, self.profile.get(channel).value_at(from, to, progress, 0.0));
+        }
+        values
+    }
+}
+
+/// Steps played in a loop, e.g. "cycle these 12 patterns to the beat". Clones share the same chase.
+///
+/// Like a [`Playback`](super::Playback), it is a [`FrameSource`]; start and
+/// stop take effect on the next rendered frame.
+#[derive(Clone)]
+pub struct Chase {
+    state: Arc<Mutex<ChaseState>>,
+}
+
+impl Chase {
+    /// A chase that runs `steps` forward, forever, with their own times.
+    pub fn new(steps: Vec<Step>) -> Result<Self, Box<dyn Error>> {
+        if steps.is_empty() {
+            return Err("a chase needs at least one step".into());
+        }
+        Ok(Chase {
+            state: Arc::new(Mutex::new(ChaseState {
+                steps,
+                direction: Direction::Forward,
+                loops: None,
+                timing: ChaseTiming::Steps,
+                profile: FadeProfile::new(),
+                rng: StdRng::from_os_rng(),
+                taps: Vec::new(),
+                pending: Vec::new(),
+                run: None,
+                output: BTreeMap::new(),
+            })),
+        })
+    }
+
+    pub fn with_direction(self, direction: Direction) -> Self {
+        self.state.lock().unwrap().direction = direction;
+        self
+    }
+
+    /// Stops after `loops` passes through the steps (there and back for [`Direction::Bounce`],
+    /// as many steps as there are for [`Direction::Random`]), holding the last step.
+    pub fn with_loops(self, loops: u32) -> Self {
+        self.state.lock().unwrap().loops = Some(loops);
+        self
+    }
+
+    pub fn with_timing(self, timing: ChaseTiming) -> Self {
+        self.state.lock().unwrap().timing = timing;
+        self
+    }
+
+    /// One step per beat at `bpm`, snapping from step to step.
+    pub fn with_bpm(self, bpm: f32) -> Self {
+        self.with_timing(ChaseTiming::Tempo { bpm, beats: 1.0, fade: 0.0 })
+    }
+
+    /// Fades channels the way `profile` says; without one every channel fades.
+    pub fn with_profile(self, profile: FadeProfile) -> Self {
+        self.state.lock().unwrap().profile = profile;
+        self
+    }
+
+    /// Makes [`Direction::Random`] repeatable.
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.state.lock().unwrap().rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Starts from the first step (the last for [`Direction::Reverse`]), restarting if running.
+    pub fn start(&self) {
+        self.state.lock().unwrap().pending.push(Command::Start);
+    }
+
+    /// Releases the chase's channels back to the base state.
+    pub fn stop(&self) {
+        self.state.lock().unwrap().pending.push(Command::Stop);
+    }
+
+    /// Tap tempo: call on each beat.
+    pub fn tap(&self) {
+        self.tap_at(Instant::now());
+    }
+
+    /// Tap tempo with an explicit time. Two or more taps less than [`TAP_RESET`]
+    /// apart set the BPM, switching to tempo timing if the chase used step times.
+    pub fn tap_at(&self, at: Instant) {
+        let mut state = self.state.lock().unwrap();
+        if state.taps.last().is_some_and(|&last| at.saturating_duration_since(last) > TAP_RESET) {
+            state.taps.clear();
+        }
+        state.taps.push(at);
+        if state.taps.len() > TAP_HISTORY {
+            state.taps.remove(0);
+        }
+        if let (Some(&first), Some(&last)) = (state.taps.first(), state.taps.last())
+            && state.taps.len() >= 2
+        {
+            let beat = (last - first).as_secs_f32() / (state.taps.len() - 1) as f32;
+            if beat > 0.0 {
+                state.set_bpm(60.0 / beat);
+            }
+        }
+    }
+
+    pub fn set_bpm(&self, bpm: f32) {
+        self.state.lock().unwrap().set_bpm(bpm);
+    }
+
+    /// The tempo, if the chase is timed by one.
+    pub fn bpm(&self) -> Option<f32> {
+        match self.state.lock().unwrap().timing {
+            ChaseTiming::Tempo { bpm, .. } => Some(bpm),
+            ChaseTiming::Steps => None,
+        }
+    }
+
+    /// Step shown at the last rendered frame.
+    pub fn current_step(&self) -> Option<usize> {
+        self.state.lock().unwrap().run.as_ref().map(|run| run.step)
+    }
+
+    /// Whether, as of the last rendered frame, the chase was started and hadn't run out of loops or been stopped.
+    pub fn is_running(&self) -> bool {
+        self.state.lock().unwrap().run.as_ref().is_some_and(|run| !run.finished)
+    }
+}
+
+/// Any of `count` steps except `current`.
+fn random_other(rng: &mut StdRng, current: usize, count: usize) -> usize {
+    match count {
+        1 => 0,
+        _ => (current + rng.random_range(1..count)) % count,
+    }
+}
+
+impl FrameSource for Chase {
+    fn render(&mut self, now: Instant, frame: &mut DmxState) {
+        let mut state = self.state.lock().unwrap();
+        for command in std::mem::take(&mut state.pending) {
+            match command {
+                Command::Start => {
+                    let step = state.first_step();
+                    let from = state.output.clone();
+                    state.run = Some(Run {
+                        step,
+                        started: now,
+                        from,
+                        loops_done: 0,
+                        steps_taken: 0,
+                        bouncing_back: false,
+                        finished: false,
+                    });
+                }
+                Command::Stop => state.run = None,
+            }
+        }
+
+        // Steps start when they fall due, not at the frame that noticed
+        while let Some(run) = &state.run
+            && !run.finished
+        {
+            let (length, _) = state.timing.times(&state.steps[run.step]);
+            let Some(due) = run.started.checked_add(length).filter(|&due| due <= now) else {
+                break;
+            };
+            state.advance(due);
+        }
+
+        state.output = state.values_at(now, frame);
+        for (&channel, &value) in &state.output {
+            frame.set_channel(channel, value);
+        }
+    }
+}
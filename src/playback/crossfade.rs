@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use crate::dmx::DmxState;
+use crate::fixture::{FadeBehaviour, Fixture};
+use crate::patch::Patch;
+
+/// [`FadeBehaviour`] of each universe channel, usually taken from the patch.
+/// Channels it doesn't know about fade.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FadeProfile {
+    behaviours: BTreeMap<usize, FadeBehaviour>,
+}
+
+impl FadeProfile {
+    pub fn new() -> Self {
+        FadeProfile { behaviours: BTreeMap::new() }
+    }
+
+    /// What every fixture in `patch` declares for its channels.
+    pub fn from_patch(patch: &Patch) -> Self {
+        let mut profile = FadeProfile::new();
+        for patched in patch.fixtures() {
+            profile.add_fixture(patched.address(), patched.fixture());
+        }
+        profile
+    }
+
+    /// Takes the behaviours `fixture` declares, for its channels starting at `address` (1-based).
+    pub fn add_fixture(&mut self, address: usize, fixture: &dyn Fixture) {
+        for (offset, behaviour) in fixture.fade_behaviours().into_iter().enumerate() {
+            self.set(address + offset, behaviour);
+        }
+    }
+
+    pub fn with_fixture(mut self, address: usize, fixture: &dyn Fixture) -> Self {
+        self.add_fixture(address, fixture);
+        self
+    }
+
+    pub fn set(&mut self, channel: usize, behaviour: FadeBehaviour) {
+        self.behaviours.insert(channel, behaviour);
+    }
+
+    pub fn get(&self, channel: usize) -> &FadeBehaviour {
+        self.behaviours.get(&channel).unwrap_or(&FadeBehaviour::Fade)
+    }
+}
+
+/// A fade between two whole universes, honouring each channel's [`FadeBehaviour`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Crossfade {
+    pub from: DmxState,
+    pub to: DmxState,
+    pub profile: FadeProfile,
+    /// Point in the fade (0.0-1.0) where snapping channels switch to the new value.
+    pub snap_at: f32,
+}
+
+impl Crossfade {
+    /// A crossfade whose snapping channels switch right at the start.
+    pub fn new(from: DmxState, to: DmxState, profile: FadeProfile) -> Self {
+        Crossfade { from, to, profile, snap_at: 0.0 }
+    }
+
+    pub fn with_snap_at(self, snap_at: f32) -> Self {
+        Crossfade { snap_at, ..self }
+    }
+
+    /// The universe `progress` (0.0-1.0) of the way through; as long as `to`.
+    pub fn at(&self, progress: f32) -> DmxState {
+        let channels = self
+            .to
+            .channels
+            .iter()
+            .enumerate()
+            .map(|(index, &to)| {
+                let from = self.from.channels.get(index).copied().unwrap_or(0);
+                self.profile.get(index + 1).value_at(from, to, progress, self.snap_at)
+            })
+            .collect();
+        DmxState { channels }
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::time::Duration;
+
+use super::fixture_values;
+use crate::fixture::Fixture;
+use crate::patch::Patch;
+
+/// What happens after a cue has been triggered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Follow {
+    /// Wait for the next GO.
+    #[default]
+    Manual,
+    /// GO the next cue this long after this one was triggered.
+    After(Duration),
+    /// GO the next cue as soon as this one's delay and fades are over.
+    Auto,
+}
+
+/// A stored look: values for some channels, and how to get there.
+///
+/// Channels a cue doesn't store keep whatever earlier cues in the list set
+/// (tracking), so a cue only needs to hold what it changes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cue {
+    pub name: String,
+    /// 1-based universe channel to value.
+    pub values: BTreeMap<usize, u8>,
+    /// Fade time for channels that go up.
+    pub fade_in: Duration,
+    /// Fade time for channels that go down, including ones released back to the base state.
+    pub fade_out: Duration,
+    /// Wait between GO and the start of the fades.
+    pub delay: Duration,
+    /// Point in each fade (0.0-1.0) where channels that snap switch to the new value.
+    pub snap_at: f32,
+    pub follow: Follow,
+}
+
+impl Cue {
+    /// An empty cue that snaps in on GO.
+    pub fn new(name: &str) -> Self {
+        Cue {
+            name: name.to_string(),
+            values: BTreeMap::new(),
+            fade_in: Duration::ZERO,
+            fade_out: Duration::ZERO,
+            delay: Duration::ZERO,
+            snap_at: 0.0,
+            follow: Follow::Manual,
+        }
+    }
+
+    /// Sets both fade times.
+    pub fn with_fade(self, fade: Duration) -> Self {
+        Cue { fade_in: fade, fade_out: fade, ..self }
+    }
+
+    pub fn with_fade_in(self, fade_in: Duration) -> Self {
+        Cue { fade_in, ..self }
+    }
+
+    pub fn with_fade_out(self, fade_out: Duration) -> Self {
+        Cue { fade_out, ..self }
+    }
+
+    pub fn with_delay(self, delay: Duration) -> Self {
+        Cue { delay, ..self }
+    }
+
+    pub fn with_snap_at(self, snap_at: f32) -> Self {
+        Cue { snap_at, ..self }
+    }
+
+    pub fn with_follow(self, follow: Follow) -> Self {
+        Cue { follow, ..self }
+    }
+
+    pub fn with_channel(mut self, channel: usize, value: u8) -> Self {
+        self.values.insert(channel, value);
+        self
+    }
+
+    /// Stores every channel of `fixture` as patched at `address` (1-based).
+    pub fn with_fixture(mut self, address: usize, fixture: &dyn Fixture) -> Result<Self, Box<dyn Error>> {
+        self.values.extend(fixture_values(address, fixture)?);
+        Ok(self)
+    }
+
+    /// Stores the current values of every fixture in `patch`.
+    pub fn with_patch(mut self, patch: &Patch) -> Self {
+        let universe = patch.render();
+        for patched in patch.fixtures() {
+            for channel in patched.address()..=patched.last_channel() {
+                self.values.insert(channel, universe.channels[channel - 1]);
+            }
+        }
+        self
+    }
+
+    /// Time from GO until every fade has finished.
+    pub fn duration(&self) -> Duration {
+        self.delay + self.fade_in.max(self.fade_out)
+    }
+}
+
+/// Cues played in order by a [`Playback`](super::Playback).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CueList {
+    pub name: String,
+    cues: Vec<Cue>,
+}
+
+impl CueList {
+    pub fn new(name: &str) -> Self {
+        CueList { name: name.to_string(), cues: Vec::new() }
+    }
+
+    /// Appends a cue and returns its index.
+    pub fn push(&mut self, cue: Cue) -> usize {
+        self.cues.push(cue);
+        self.cues.len() - 1
+    }
+
+    pub fn with_cue(mut self, cue: Cue) -> Self {
+        self.push(cue);
+        self
+    }
+
+    pub fn cues(&self) -> &[Cue] {
+        &self.cues
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Cue> {
+        self.cues.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cues.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cues.is_empty()
+    }
+
+    /// Index of the first cue called `name`.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.cues.iter().position(|cue| cue.name == name)
+    }
+
+    /// The full look at cue `index`: its own values on top of everything tracked from earlier cues.
+    pub fn look(&self, index: usize) -> BTreeMap<usize, u8> {
+        let mut look = BTreeMap::new();
+        for cue in self.cues.iter().take(index + 1) {
+            look.extend(cue.values.iter().map(|(&channel, &value)| (channel, value)));
+        }
+        look
+    }
+}
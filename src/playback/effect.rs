@@ -0,0 +1,262 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::error::Error;
+use std::f32::consts::TAU;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use super::Spread;
+use crate::dmx::{DmxState, FrameSource};
+use crate::patch::Patch;
+
+/// Shape of an [`Lfo`], as a value between -1.0 and 1.0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Waveform {
+    Sine,
+    /// Rises from 0 like the sine, with straight sides.
+    Triangle,
+    /// Ramps from -1 up to 1, then drops back.
+    Saw,
+    /// 1 for the first half of each cycle, -1 for the second.
+    Square,
+    /// Wanders randomly, never faster than the triangle at the same rate.
+    RandomWalk,
+}
+
+impl Waveform {
+    /// Value at `position` (cycles) for the periodic shapes; random walks are stateful.
+    fn periodic(self, position: f32) -> f32 {
+        let x = position.rem_euclid(1.0);
+        match self {
+            Waveform::Sine => (x * TAU).sin(),
+            Waveform::Triangle if x < 0.25 => 4.0 * x,
+            Waveform::Triangle if x < 0.75 => 2.0 - 4.0 * x,
+            Waveform::Triangle => 4.0 * x - 4.0,
+            Waveform::Saw => 2.0 * x - 1.0,
+            Waveform::Square if x < 0.5 => 1.0,
+            Waveform::Square => -1.0,
+            Waveform::RandomWalk => 0.0,
+        }
+    }
+}
+
+/// How an [`Lfo`] combines with what's already in the frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EffectMode {
+    /// Adds to the value underneath, e.g. a cue's size, so effects stack.
+    #[default]
+    Relative,
+    /// Replaces the value underneath.
+    Absolute,
+}
+
+/// A waveform generator for one channel: `offset + amplitude * waveform`,
+/// clamped to `range`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lfo {
+    pub waveform: Waveform,
+    /// Cycles per second.
+    pub rate: f32,
+    /// Where in the cycle it starts, 0.0-1.0.
+    pub phase: f32,
+    /// Swing either side of the centre, in DMX values.
+    pub amplitude: f32,
+    /// Centre of the swing: added to the value underneath, or the absolute centre.
+    pub offset: f32,
+    /// Output is clamped to this, e.g. `0..=127` to keep CH13 in its position range.
+    /// Private so it stays ordered; see [`with_range`](Lfo::with_range).
+    range: RangeInclusive<u8>,
+    pub mode: EffectMode,
+}
+
+impl Lfo {
+    /// Swings `amplitude` either side of whatever value is underneath.
+    pub fn new(waveform: Waveform, rate: f32, amplitude: f32) -> Self {
+        Lfo { waveform, rate, phase: 0.0, amplitude, offset: 0.0, range: 0..=255, mode: EffectMode::Relative }
+    }
+
+    /// Sweeps between `low` and `high` regardless of what's underneath.
+    pub fn between(waveform: Waveform, rate: f32, low: u8, high: u8) -> Self {
+        let (low, high) = (low.min(high), low.max(high));
+        Lfo {
+            waveform,
+            rate,
+            phase: 0.0,
+            amplitude: (high - low) as f32 / 2.0,
+            offset: (low as f32 + high as f32) / 2.0,
+            range: low..=high,
+            mode: EffectMode::Absolute,
+        }
+    }
+
+    pub fn with_phase(self, phase: f32) -> Self {
+        Lfo { phase, ..self }
+    }
+
+    pub fn with_offset(self, offset: f32) -> Self {
+        Lfo { offset, ..self }
+    }
+
+    /// Clamps the output to `range`; a reversed range is taken the right way round.
+    pub fn with_range(self, range: RangeInclusive<u8>) -> Self {
+        let (&start, &end) = (range.start(), range.end());
+        Lfo { range: start.min(end)..=start.max(end), ..self }
+    }
+
+    pub fn range(&self) -> RangeInclusive<u8> {
+        self.range.clone()
+    }
+
+    /// The channel value for a waveform value `wave` (-1.0-1.0) over `underneath`.
+    fn value(&self, wave: f32, underneath: u8) -> u8 {
+        let centre = match self.mode {
+            EffectMode::Relative => underneath as f32 + self.offset,
+            EffectMode::Absolute => self.offset,
+        };
+        let value = (centre + self.amplitude * wave).round().clamp(0.0, 255.0) as u8;
+        value.clamp(*self.range.start(), *self.range.end())
+    }
+}
+
+/// Handle for removing an effect from [`Effects`].
+pub type EffectId = usize;
+
+struct Layer {
+    id: EffectId,
+    channel: usize,
+    lfo: Lfo,
+    /// Set by the first frame rendered after the layer was added.
+    started: Option<Instant>,
+    walk: f32,
+    last_render: Option<Instant>,
+}
+
+struct EffectsState {
+    layers: Vec<Layer>,
+    next_id: EffectId,
+    rng: StdRng,
+}
+
+impl EffectsState {
+    fn push(&mut self, channel: usize, lfo: Lfo) -> EffectId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.layers.push(Layer { id, channel, lfo, started: None, walk: 0.0, last_render: None });
+        id
+    }
+}
+
+/// A stack of [`Lfo`]s applied in the order they were added. Clones share the same stack.
+///
+/// Add it to a refresh after a [`Playback`](super::Playback) to run effects
+/// over the cue: relative effects move around the cue's values.
+#[derive(Clone)]
+pub struct Effects {
+    state: Arc<Mutex<EffectsState>>,
+}
+
+impl Default for Effects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effects {
+    pub fn new() -> Self {
+        Effects {
+            state: Arc::new(Mutex::new(EffectsState { layers: Vec::new(), next_id: 0, rng: StdRng::from_os_rng() })),
+        }
+    }
+
+    /// Makes random walks repeatable.
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.state.lock().unwrap().rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Runs `lfo` on universe `channel` (1-based), from the next frame on.
+    pub fn add(&self, channel: usize, lfo: Lfo) -> EffectId {
+        self.state.lock().unwrap().push(channel, lfo)
+    }
+
+    /// Runs `lfo` on each of `channels`, shifted per channel by `spread`. The
+    /// copies start on the same frame, so their phases stay locked together.
+    pub fn add_spread(&self, channels: &[usize], lfo: Lfo, spread: &Spread) -> Vec<EffectId> {
+        let mut state = self.state.lock().unwrap();
+        spread
+            .offsets(channels.len())
+            .into_iter()
+            .zip(channels)
+            .map(|(member, &channel)| {
+                let lfo = Lfo { phase: lfo.phase + member.phase, offset: lfo.offset + member.offset, ..lfo.clone() };
+                state.push(channel, lfo)
+            })
+            .collect()
+    }
+
+    /// Runs `lfo` on `channel` (e.g. "Horizontal movement") of every member of a patch group.
+    pub fn add_to_group(
+        &self,
+        patch: &Patch,
+        group: &str,
+        channel: &str,
+        lfo: Lfo,
+        spread: &Spread,
+    ) -> Result<Vec<EffectId>, Box<dyn Error>> {
+        let channels = patch.group_channels(group, channel)?;
+        Ok(self.add_spread(&channels, lfo, spread))
+    }
+
+    /// Stops an effect; the channel shows what's underneath again. False if it was already gone.
+    pub fn remove(&self, id: EffectId) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let before = state.layers.len();
+        state.layers.retain(|layer| layer.id != id);
+        state.layers.len() != before
+    }
+
+    pub fn clear(&self) {
+        self.state.lock().unwrap().layers.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().layers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl FrameSource for Effects {
+    fn render(&mut self, now: Instant, frame: &mut DmxState) {
+        let state = &mut *self.state.lock().unwrap();
+        for layer in &mut state.layers {
+            let started = *layer.started.get_or_insert(now);
+            let wave = match layer.lfo.waveform {
+                Waveform::RandomWalk => {
+                    let elapsed = layer.last_render.map(|last| now.saturating_duration_since(last)).unwrap_or_default();
+                    let most = 4.0 * layer.lfo.rate * elapsed.as_secs_f32();
+                    let mut walk = layer.walk + state.rng.random_range(-1.0..=1.0) * most;
+                    // Bounce off the ends rather than sticking to them
+                    if walk > 1.0 {
+                        walk = 2.0 - walk;
+                    } else if walk < -1.0 {
+                        walk = -2.0 - walk;
+                    }
+                    layer.walk = walk.clamp(-1.0, 1.0);
+                    layer.walk
+                }
+                waveform => {
+                    let elapsed = now.saturating_duration_since(started).as_secs_f32();
+                    waveform.periodic(elapsed * layer.lfo.rate + layer.lfo.phase)
+                }
+            };
+            layer.last_render = Some(now);
+            if let Some(underneath) = frame.get_channel(layer.channel) {
+                frame.set_channel(layer.channel, layer.lfo.value(wave, underneath));
+            }
+        }
+    }
+}
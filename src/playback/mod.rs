@@ -0,0 +1,242 @@
+//! Cue lists, chases and effects played back into a refreshed universe.
+//!
+//! A [`Playback`] is a [`FrameSource`]: attach a clone to a running refresh
+//! with [`DmxHandle::add_source`](crate::dmx::DmxHandle::add_source) and call
+//! [`go`](Playback::go) and friends from anywhere. Commands take effect on the
+//! next rendered frame, so fades are timed from the frame that starts them.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::dmx::{DmxState, FrameSource, DMX_FRAME_SIZE};
+use crate::fixture::Fixture;
+
+pub mod chase;
+pub mod crossfade;
+pub mod cue;
+pub mod effect;
+pub mod spread;
+
+pub use chase::{Chase, ChaseTiming, Direction, Step};
+pub use crossfade::{Crossfade, FadeProfile};
+pub use cue::{Cue, CueList, Follow};
+pub use effect::{EffectId, EffectMode, Effects, Lfo, Waveform};
+pub use spread::{MemberOffset, Spread, SpreadPattern};
+
+/// Every channel of `fixture` as patched at `address` (1-based), by universe channel.
+fn fixture_values(address: usize, fixture: &dyn Fixture) -> Result<BTreeMap<usize, u8>, Box<dyn Error>> {
+    let footprint = fixture.footprint();
+    if address < 1 || address + footprint - 1 > DMX_FRAME_SIZE {
+        return Err(format!("{} channels at address {} don't fit in the universe", footprint, address).into());
+    }
+    let mut slots = vec![0u8; footprint];
+    fixture.encode(&mut slots);
+    Ok(slots.into_iter().enumerate().map(|(offset, value)| (address + offset, value)).collect())
+}
+
+enum Command {
+    Go,
+    Back,
+    Goto(usize),
+    Stop,
+}
+
+/// One channel's way from the previous output to the new cue. `None` means
+/// the base state underneath the playback.
+struct ChannelFade {
+    from: Option<u8>,
+    to: Option<u8>,
+    duration: Duration,
+}
+
+struct ActiveCue {
+    index: usize,
+    started: Instant,
+    channels: BTreeMap<usize, ChannelFade>,
+}
+
+struct PlaybackState {
+    list: CueList,
+    profile: FadeProfile,
+    pending: Vec<Command>,
+    active: Option<ActiveCue>,
+    /// Values written in the last frame; the next cue fades from these.
+    output: BTreeMap<usize, u8>,
+    last_render: Option<Instant>,
+}
+
+impl PlaybackState {
+    fn trigger(&mut self, index: usize, at: Instant) {
+        let cue = &self.list.cues()[index];
+        let look = self.list.look(index);
+        let mut channels = BTreeMap::new();
+        for &channel in look.keys().chain(self.output.keys()) {
+            let from = self.output.get(&channel).copied();
+            let to = look.get(&channel).copied();
+            let duration = match (from, to) {
+                (Some(from), Some(to)) if to < from => cue.fade_out,
+                (_, None) => cue.fade_out,
+                _ => cue.fade_in,
+            };
+            channels.insert(channel, ChannelFade { from, to, duration });
+        }
+        self.active = Some(ActiveCue { index, started: at, channels });
+    }
+
+    /// When the active cue's follow fires, if it has one and there is a next cue.
+    fn follow_due(&self) -> Option<Instant> {
+        let active = self.active.as_ref()?;
+        if active.index + 1 >= self.list.len() {
+            return None;
+        }
+        let cue = &self.list.cues()[active.index];
+        match cue.follow {
+            Follow::Manual => None,
+            Follow::After(wait) => Some(active.started + wait),
+            Follow::Auto => Some(active.started + cue.duration()),
+        }
+    }
+
+    /// Channel values at `at`; released channels drop out once faded back to `base`.
+    fn values_at(&self, at: Instant, base: &DmxState) -> BTreeMap<usize, u8> {
+        let Some(active) = &self.active else {
+            return BTreeMap::new();
+        };
+        let cue = &self.list.cues()[active.index];
+        let elapsed = at.saturating_duration_since(active.started);
+        let mut values = BTreeMap::new();
+        for (&channel, fade) in &active.channels {
+            // `None` while the cue's delay runs
+            let progress = match elapsed.checked_sub(cue.delay) {
+                None => None,
+                Some(_) if fade.duration.is_zero() => Some(1.0),
+                Some(fading) => Some((fading.as_secs_f32() / fade.duration.as_secs_f32()).min(1.0)),
+            };
+            if fade.to.is_none() && progress == Some(1.0) {
+                continue;
+            }
+            let base_value = base.get_channel(channel).unwrap_or(0);
+            let from = fade.from.unwrap_or(base_value);
+            let to = fade.to.unwrap_or(base_value);
+            let value = match progress {
+                None => from,
+                Some(progress) => self.profile.get(channel).value_at(from, to, progress, cue.snap_at),
+            };
+            values.insert(channel, value);
+        }
+        values
+    }
+}
+
+/// Plays a [`CueList`]. Clones share the same playback.
+#[derive(Clone)]
+pub struct Playback {
+    state: Arc<Mutex<PlaybackState>>,
+}
+
+impl Playback {
+    pub fn new(list: CueList) -> Self {
+        Playback {
+            state: Arc::new(Mutex::new(PlaybackState {
+                list,
+                profile: FadeProfile::new(),
+                pending: Vec::new(),
+                active: None,
+                output: BTreeMap::new(),
+                last_render: None,
+            })),
+        }
+    }
+
+    /// Fades channels the way `profile` says, e.g. [`FadeProfile::from_patch`];
+    /// without one every channel fades.
+    pub fn with_profile(self, profile: FadeProfile) -> Self {
+        self.state.lock().unwrap().profile = profile;
+        self
+    }
+
+    /// Starts the next cue, or the first if nothing is playing. Does nothing on the last cue.
+    pub fn go(&self) {
+        self.state.lock().unwrap().pending.push(Command::Go);
+    }
+
+    /// Starts the previous cue with its own times.
+    pub fn back(&self) {
+        self.state.lock().unwrap().pending.push(Command::Back);
+    }
+
+    /// Starts cue `index` from wherever the output is now.
+    pub fn goto(&self, index: usize) -> Result<(), Box<dyn Error>> {
+        let mut state = self.state.lock().unwrap();
+        if index >= state.list.len() {
+            return Err(format!("cue list '{}' has no cue {}", state.list.name, index).into());
+        }
+        state.pending.push(Command::Goto(index));
+        Ok(())
+    }
+
+    /// Releases every channel back to the base state at once.
+    pub fn stop(&self) {
+        self.state.lock().unwrap().pending.push(Command::Stop);
+    }
+
+    /// Index of the cue playing as of the last rendered frame.
+    pub fn current(&self) -> Option<usize> {
+        self.state.lock().unwrap().active.as_ref().map(|active| active.index)
+    }
+
+    /// Whether the current cue was still in its delay or fades at the last rendered frame.
+    pub fn is_fading(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        match (&state.active, state.last_render) {
+            (Some(active), Some(now)) => now < active.started + state.list.cues()[active.index].duration(),
+            _ => false,
+        }
+    }
+
+    pub fn cue_list(&self) -> CueList {
+        self.state.lock().unwrap().list.clone()
+    }
+}
+
+impl FrameSource for Playback {
+    fn render(&mut self, now: Instant, frame: &mut DmxState) {
+        let mut state = self.state.lock().unwrap();
+        for command in std::mem::take(&mut state.pending) {
+            let current = state.active.as_ref().map(|active| active.index);
+            let target = match (command, current) {
+                (Command::Go, None) if !state.list.is_empty() => Some(0),
+                (Command::Go, Some(index)) if index + 1 < state.list.len() => Some(index + 1),
+                (Command::Back, Some(index)) if index > 0 => Some(index - 1),
+                (Command::Goto(index), _) => Some(index),
+                (Command::Stop, _) => {
+                    state.active = None;
+                    state.output.clear();
+                    None
+                }
+                _ => None,
+            };
+            if let Some(index) = target {
+                state.output = state.values_at(now, frame);
+                state.trigger(index, now);
+            }
+        }
+
+        // Follows are started at the time they fell due, not at the frame that noticed
+        while let Some(due) = state.follow_due()
+            && due <= now
+        {
+            let next = state.active.as_ref().map(|active| active.index + 1).unwrap_or(0);
+            state.output = state.values_at(due, frame);
+            state.trigger(next, due);
+        }
+
+        state.output = state.values_at(now, frame);
+        for (&channel, &value) in &state.output {
+            frame.set_channel(channel, value);
+        }
+        state.last_render = Some(now);
+    }
+}
@@ -0,0 +1,119 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/// Order an effect runs through the members of a group.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpreadPattern {
+    /// First to last, like a wave rolling along the row.
+    #[default]
+    Wave,
+    /// From the middle outwards; members the same distance from the middle move together.
+    Mirror,
+    /// A shuffled order, fixed by the seed.
+    Random { seed: u64 },
+}
+
+/// How one effect is distributed over a group, like the phase and fan
+/// settings of a moving-head effect engine.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spread {
+    pub pattern: SpreadPattern,
+    /// Phase difference across the whole group, in cycles. 1.0 spaces the
+    /// members evenly over one cycle; 0.0 keeps them in step.
+    pub phase: f32,
+    /// Offset spread in DMX values: the first member in the pattern's order
+    /// gets `-fan`, the last `+fan`, e.g. to fan positions out.
+    pub fan: f32,
+    /// Neighbouring members that move as one (1 = each on its own).
+    pub grouping: usize,
+    /// Splits the group into this many wings, each running the pattern
+    /// again, every other one mirrored.
+    pub wings: usize,
+}
+
+/// What [`Spread`] gives one member: added to the effect's phase and offset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MemberOffset {
+    pub phase: f32,
+    pub offset: f32,
+}
+
+impl Default for Spread {
+    fn default() -> Self {
+        Spread { pattern: SpreadPattern::Wave, phase: 0.0, fan: 0.0, grouping: 1, wings: 1 }
+    }
+}
+
+impl Spread {
+    /// Phase rising from the first member to the last.
+    pub fn wave(phase: f32) -> Self {
+        Spread { phase, ..Spread::default() }
+    }
+
+    pub fn mirror(phase: f32) -> Self {
+        Spread { pattern: SpreadPattern::Mirror, phase, ..Spread::default() }
+    }
+
+    pub fn random(phase: f32, seed: u64) -> Self {
+        Spread { pattern: SpreadPattern::Random { seed }, phase, ..Spread::default() }
+    }
+
+    /// Members in step, with offsets fanned out by `fan` either side.
+    pub fn fan(fan: f32) -> Self {
+        Spread { fan, ..Spread::default() }
+    }
+
+    pub fn with_fan(self, fan: f32) -> Self {
+        Spread { fan, ..self }
+    }
+
+    pub fn with_grouping(self, grouping: usize) -> Self {
+        Spread { grouping, ..self }
+    }
+
+    pub fn with_wings(self, wings: usize) -> Self {
+        Spread { wings, ..self }
+    }
+
+    /// Phase and offset for each of `members` members, in member order.
+    pub fn offsets(&self, members: usize) -> Vec<MemberOffset> {
+        let wings = self.wings.clamp(1, members.max(1));
+        let wing_size = members.div_ceil(wings);
+        let grouping = self.grouping.max(1);
+        let slots = wing_size.div_ceil(grouping).max(1);
+
+        // Where each slot comes in the pattern's order, out of `steps`
+        let (order, steps): (Vec<usize>, usize) = match self.pattern {
+            SpreadPattern::Wave => ((0..slots).collect(), slots),
+            SpreadPattern::Mirror => {
+                // Rank by distance from the middle: 0 for the middle slot(s)
+                let rank = |slot: usize| (2 * slot).abs_diff(slots - 1) / 2;
+                ((0..slots).map(rank).collect(), slots.div_ceil(2))
+            }
+            SpreadPattern::Random { seed } => {
+                let mut order: Vec<usize> = (0..slots).collect();
+                order.shuffle(&mut StdRng::seed_from_u64(seed));
+                (order, slots)
+            }
+        };
+
+        (0..members)
+            .map(|member| {
+                let wing = member / wing_size;
+                let mut position = member % wing_size;
+                if wing % 2 == 1 {
+                    // Mirror within this wing, which may be the short last one
+                    let length = wing_size.min(members - wing * wing_size);
+                    position = length - 1 - position;
+                }
+                let step = order[(position / grouping).min(slots - 1)];
+                let fan = match steps {
+                    1 => 0.0,
+                    _ => self.fan * (2.0 * step as f32 / (steps - 1) as f32 - 1.0),
+                };
+                MemberOffset { phase: self.phase * step as f32 / steps as f32, offset: fan }
+            })
+            .collect()
+    }
+}
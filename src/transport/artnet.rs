@@ -0,0 +1,134 @@
+use std::error::Error;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+
+use super::{DmxTransport, TransportCapabilities};
+use crate::dmx::DMX_FRAME_SIZE;
+
+pub const ARTNET_PORT: u16 = 6454;
+pub const ARTNET_ID: &[u8; 8] = b"Art-Net\0";
+pub const OP_DMX: u16 = 0x5000;
+pub const PROTOCOL_VERSION: u16 = 14;
+
+/// 15-bit Art-Net Port-Address: Net (7 bits), Sub-Net (4 bits), Universe (4 bits).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArtNetAddress {
+    pub net: u8,
+    pub subnet: u8,
+    pub universe: u8,
+}
+
+impl ArtNetAddress {
+    pub fn new(net: u8, subnet: u8, universe: u8) -> Result<Self, Box<dyn Error>> {
+        if net > 0x7F {
+            return Err("Art-Net net must be between 0 and 127".into());
+        }
+        if subnet > 0x0F || universe > 0x0F {
+            return Err("Art-Net sub-net and universe must be between 0 and 15".into());
+        }
+        Ok(ArtNetAddress { net, subnet, universe })
+    }
+
+    pub fn from_port_address(port_address: u16) -> Self {
+        ArtNetAddress {
+            net: ((port_address >> 8) & 0x7F) as u8,
+            subnet: ((port_address >> 4) & 0x0F) as u8,
+            universe: (port_address & 0x0F) as u8,
+        }
+    }
+
+    pub fn port_address(&self) -> u16 {
+        (self.net as u16) << 8 | (self.subnet as u16) << 4 | self.universe as u16
+    }
+}
+
+/// Builds an ArtDmx packet. Odd slot counts are padded, as the protocol requires an even length.
+pub fn encode_art_dmx(sequence: u8, address: ArtNetAddress, slots: &[u8]) -> Vec<u8> {
+    let slots = &slots[..slots.len().min(DMX_FRAME_SIZE)];
+    let length = (slots.len() + slots.len() % 2).max(2);
+
+    let mut packet = Vec::with_capacity(18 + length);
+    packet.extend_from_slice(ARTNET_ID);
+    packet.extend_from_slice(&OP_DMX.to_le_bytes());
+    packet.extend_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+    packet.push(sequence);
+    packet.push(0); // Physical input port, informational only
+    packet.push(address.subnet << 4 | address.universe);
+    packet.push(address.net);
+    packet.extend_from_slice(&(length as u16).to_be_bytes());
+    packet.extend_from_slice(slots);
+    packet.resize(18 + length, 0);
+    packet
+}
+
+/// Sends universes as ArtDmx packets to one node (unicast) or a whole subnet (broadcast).
+pub struct ArtNetTransport {
+    target: SocketAddr,
+    address: ArtNetAddress,
+    broadcast: bool,
+    socket: Option<UdpSocket>,
+    sequence: u8,
+}
+
+impl ArtNetTransport {
+    /// Sends to a single node, e.g. `2.0.0.10:6454`.
+    pub fn unicast(target: SocketAddr, address: ArtNetAddress) -> Self {
+        ArtNetTransport { target, address, broadcast: false, socket: None, sequence: 0 }
+    }
+
+    /// Sends to the directed broadcast address of an Art-Net subnet, e.g. `2.255.255.255`.
+    pub fn broadcast(broadcast_ip: Ipv4Addr, address: ArtNetAddress) -> Self {
+        let target = SocketAddr::V4(SocketAddrV4::new(broadcast_ip, ARTNET_PORT));
+        ArtNetTransport { target, address, broadcast: true, socket: None, sequence: 0 }
+    }
+
+    pub fn address(&self) -> ArtNetAddress {
+        self.address
+    }
+
+    /// Sequence number of the last packet sent; 0 before the first one.
+    pub fn sequence(&self) -> u8 {
+        self.sequence
+    }
+
+    fn next_sequence(&mut self) -> u8 {
+        // 0 tells receivers to ignore ordering, so the counter runs 1..=255.
+        self.sequence = if self.sequence == 0xFF { 1 } else { self.sequence + 1 };
+        self.sequence
+    }
+}
+
+impl DmxTransport for ArtNetTransport {
+    fn open(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.socket.is_none() {
+            let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+            socket.set_broadcast(self.broadcast)?;
+            self.socket = Some(socket);
+        }
+        Ok(())
+    }
+
+    fn send_universe(&mut self, slots: &[u8]) -> Result<(), Box<dyn Error>> {
+        if self.socket.is_none() {
+            return Err("Art-Net socket is not open".into());
+        }
+        let sequence = self.next_sequence();
+        let packet = encode_art_dmx(sequence, self.address, slots);
+        if let Some(socket) = &self.socket {
+            socket.send_to(&packet, self.target)?;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        self.socket = None;
+        Ok(())
+    }
+
+    fn capabilities(&self) -> TransportCapabilities {
+        TransportCapabilities {
+            max_slots: DMX_FRAME_SIZE,
+            generates_break: false,
+            networked: true,
+        }
+    }
+}
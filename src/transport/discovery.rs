@@ -0,0 +1,154 @@
+//! Finding DMX adapters without disturbing other serial devices.
+//!
+//! [`discover_ports`] only reads what the OS reports about each port. Probing
+//! is opt-in: [`probe_port`] opens the port and asks it, Enttec-style, for its
+//! parameters and serial number. That request reaches whatever is attached, so
+//! only probe ports that plausibly are DMX adapters.
+
+use serialport::{SerialPort, SerialPortInfo, SerialPortType};
+use std::error::Error;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use super::enttec::{
+    decode_serial_number, encode_message, read_message, WidgetParameters, LABEL_GET_SERIAL_NUMBER,
+    LABEL_GET_WIDGET_PARAMETERS,
+};
+use super::SerialAdapter;
+
+/// USB vendor ID of Future Technology Devices (FTDI), used by most DMX cables and widgets.
+pub const FTDI_VID: u16 = 0x0403;
+
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_millis(300);
+
+// Product strings of widgets that speak the Enttec DMX USB Pro API.
+const ENTTEC_COMPATIBLE_PRODUCTS: [&str; 3] = ["DMX USB PRO", "ULTRADMX", "DMXIS"];
+
+/// What answered an opt-in probe.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProbeResult {
+    /// An Enttec DMX USB Pro compatible widget answered.
+    EnttecPro { firmware_version: u16, serial_number: String },
+    /// The port opened but nothing answered; an Open DMX cable behaves like this.
+    NoReply,
+    /// The port could not be opened, e.g. it is in use.
+    Unavailable(String),
+}
+
+/// A serial port and what is known about the device behind it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiscoveredPort {
+    pub port_name: String,
+    /// USB vendor and product ID, for USB ports.
+    pub vid: Option<u16>,
+    pub pid: Option<u16>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    /// USB serial number as the OS reports it (FTDI chips carry one, e.g. "EN123456").
+    pub serial_number: Option<String>,
+    pub is_ftdi: bool,
+    /// The descriptors name an Enttec DMX USB Pro or a compatible widget.
+    pub enttec_like: bool,
+    /// Set by [`DiscoveredPort::probe`].
+    pub probe: Option<ProbeResult>,
+}
+
+impl DiscoveredPort {
+    /// Describes a port from the OS's enumeration, without opening it.
+    pub fn from_info(info: &SerialPortInfo) -> Self {
+        let mut port = DiscoveredPort {
+            port_name: info.port_name.clone(),
+            vid: None,
+            pid: None,
+            manufacturer: None,
+            product: None,
+            serial_number: None,
+            is_ftdi: false,
+            enttec_like: false,
+            probe: None,
+        };
+        if let SerialPortType::UsbPort(usb) = &info.port_type {
+            port.vid = Some(usb.vid);
+            port.pid = Some(usb.pid);
+            port.manufacturer = usb.manufacturer.clone();
+            port.product = usb.product.clone();
+            port.serial_number = usb.serial_number.clone();
+            port.is_ftdi = usb.vid == FTDI_VID;
+
+            let product = usb.product.as_deref().unwrap_or_default().to_ascii_uppercase();
+            let manufacturer = usb.manufacturer.as_deref().unwrap_or_default().to_ascii_uppercase();
+            port.enttec_like = ENTTEC_COMPATIBLE_PRODUCTS.iter().any(|name| product.contains(name))
+                || (manufacturer.contains("ENTTEC") && !product.contains("OPEN DMX"));
+        }
+        port
+    }
+
+    /// The adapter type to drive this port with, if it looks like a DMX adapter at all.
+    /// A probe reply beats the USB descriptors.
+    pub fn likely_adapter(&self) -> Option<SerialAdapter> {
+        match &self.probe {
+            Some(ProbeResult::EnttecPro { .. }) => Some(SerialAdapter::EnttecPro),
+            Some(ProbeResult::Unavailable(_)) => None,
+            _ if self.enttec_like => Some(SerialAdapter::EnttecPro),
+            _ if self.is_ftdi => Some(SerialAdapter::OpenDmx),
+            _ => None,
+        }
+    }
+
+    /// Opens the port and asks it to identify itself, storing the result.
+    pub fn probe(&mut self, timeout: Duration) -> &ProbeResult {
+        self.probe.insert(probe_port(&self.port_name, timeout))
+    }
+}
+
+/// Lists serial ports with their USB metadata. Never opens or writes to a port.
+pub fn discover_ports() -> Result<Vec<DiscoveredPort>, Box<dyn Error>> {
+    let ports = serialport::available_ports()?;
+    Ok(ports.iter().map(DiscoveredPort::from_info).collect())
+}
+
+/// Lists serial ports and probes the ones whose USB descriptors suggest a DMX adapter.
+pub fn discover_and_probe(timeout: Duration) -> Result<Vec<DiscoveredPort>, Box<dyn Error>> {
+    let mut ports = discover_ports()?;
+    for port in ports.iter_mut().filter(|port| port.is_ftdi || port.enttec_like) {
+        port.probe(timeout);
+    }
+    Ok(ports)
+}
+
+/// Opens `port_name` and sends Enttec "Get Widget Parameters" and "Get Serial
+/// Number" requests. No DMX is sent.
+pub fn probe_port(port_name: &str, timeout: Duration) -> ProbeResult {
+    match serialport::new(port_name, 57_600).timeout(timeout).open() {
+        Ok(port) => probe(port, timeout),
+        Err(e) => ProbeResult::Unavailable(e.to_string()),
+    }
+}
+
+/// Probes a port that is already open.
+pub fn probe(mut port: Box<dyn SerialPort>, timeout: Duration) -> ProbeResult {
+    let Some(parameters) = request(&mut port, LABEL_GET_WIDGET_PARAMETERS, &[0, 0], timeout)
+        .and_then(|reply| WidgetParameters::from_payload(&reply).ok())
+    else {
+        return ProbeResult::NoReply;
+    };
+    let serial_number = request(&mut port, LABEL_GET_SERIAL_NUMBER, &[], timeout)
+        .and_then(|reply| decode_serial_number(&reply).ok())
+        .unwrap_or_default();
+    ProbeResult::EnttecPro { firmware_version: parameters.firmware_version, serial_number }
+}
+
+// Like the transport's request, but gives up at the deadline so a device that
+// keeps talking can't hold the probe forever.
+fn request(port: &mut Box<dyn SerialPort>, label: u8, payload: &[u8], timeout: Duration) -> Option<Vec<u8>> {
+    port.write_all(&encode_message(label, payload)).ok()?;
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        match read_message(port) {
+            Ok((reply_label, reply)) if reply_label == label => return Some(reply),
+            Ok(_) => continue,
+            Err(_) => return None,
+        }
+    }
+    None
+}
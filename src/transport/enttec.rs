@@ -0,0 +1,209 @@
+use serialport::SerialPort;
+use std::error::Error;
+use std::io::{Read, Write};
+use std::time::Duration;
+
+use super::{DmxTransport, TransportCapabilities};
+use crate::dmx::{DmxTiming, DMX_FRAME_SIZE};
+
+pub const START_OF_MESSAGE: u8 = 0x7E;
+pub const END_OF_MESSAGE: u8 = 0xE7;
+
+pub const LABEL_GET_WIDGET_PARAMETERS: u8 = 3;
+pub const LABEL_SET_WIDGET_PARAMETERS: u8 = 4;
+pub const LABEL_SEND_DMX_PACKET: u8 = 6;
+pub const LABEL_GET_SERIAL_NUMBER: u8 = 10;
+
+/// The widget rejects DMX packets carrying fewer than 24 slots.
+pub const MIN_DMX_SLOTS: usize = 24;
+
+/// Break and mark-after-break are set in units of 10.67µs.
+pub const TIMING_UNIT_US: f32 = 10.67;
+
+// The widget talks USB CDC, so the baud rate is ignored; 57600 is what Enttec's tools use.
+const WIDGET_BAUD_RATE: u32 = 57_600;
+
+/// Builds a widget message: 0x7E, label, payload length (LSB first), payload, 0xE7.
+pub fn encode_message(label: u8, payload: &[u8]) -> Vec<u8> {
+    let len = payload.len() as u16;
+    let mut message = Vec::with_capacity(payload.len() + 5);
+    message.push(START_OF_MESSAGE);
+    message.push(label);
+    message.push((len & 0xFF) as u8);
+    message.push((len >> 8) as u8);
+    message.extend_from_slice(payload);
+    message.push(END_OF_MESSAGE);
+    message
+}
+
+/// Reads one widget message, skipping any bytes before the start delimiter.
+/// Returns the label and the payload.
+pub fn read_message<R: Read + ?Sized>(reader: &mut R) -> Result<(u8, Vec<u8>), Box<dyn Error>> {
+    let mut byte = [0u8; 1];
+    loop {
+        reader.read_exact(&mut byte)?;
+        if byte[0] == START_OF_MESSAGE {
+            break;
+        }
+    }
+
+    let mut header = [0u8; 3];
+    reader.read_exact(&mut header)?;
+    let label = header[0];
+    let len = header[1] as usize | (header[2] as usize) << 8;
+    if len > DMX_FRAME_SIZE + 1 {
+        return Err(format!("widget message length {} is too long", len).into());
+    }
+
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload)?;
+    reader.read_exact(&mut byte)?;
+    if byte[0] != END_OF_MESSAGE {
+        return Err("widget message is missing the end delimiter".into());
+    }
+    Ok((label, payload))
+}
+
+/// Reply to "Get Widget Parameters" (label 3).
+#[derive(Clone, Debug, PartialEq)]
+pub struct WidgetParameters {
+    pub firmware_version: u16,
+    /// Break time in microseconds (the widget counts in 10.67µs units).
+    pub break_time_us: f32,
+    /// Mark-after-break time in microseconds (10.67µs units).
+    pub mab_time_us: f32,
+    /// Output rate in packets per second; 0 means as fast as possible.
+    pub refresh_rate: u8,
+}
+
+impl WidgetParameters {
+    pub fn from_payload(payload: &[u8]) -> Result<Self, Box<dyn Error>> {
+        if payload.len() < 5 {
+            return Err("widget parameters reply is too short".into());
+        }
+        Ok(WidgetParameters {
+            firmware_version: payload[0] as u16 | (payload[1] as u16) << 8,
+            break_time_us: payload[2] as f32 * TIMING_UNIT_US,
+            mab_time_us: payload[3] as f32 * TIMING_UNIT_US,
+            refresh_rate: payload[4],
+        })
+    }
+}
+
+/// Payload of "Set Widget Parameters" (label 4) for `timing`, rounded up to whole
+/// widget units and clamped to the ranges the widget accepts (break 9-127, MAB 1-127).
+pub fn timing_payload(timing: &DmxTiming, refresh_rate: u8) -> Vec<u8> {
+    let units = |us: f32| (us / TIMING_UNIT_US).ceil() as u8;
+    vec![
+        0, 0, // No user configuration block
+        units(timing.break_time.as_micros() as f32).clamp(9, 127),
+        units(timing.mark_after_break.as_micros() as f32).clamp(1, 127),
+        refresh_rate.min(40),
+    ]
+}
+
+/// Decodes the 4-byte BCD, least-significant-byte-first serial number reply (label 10).
+pub fn decode_serial_number(payload: &[u8]) -> Result<String, Box<dyn Error>> {
+    if payload.len() < 4 {
+        return Err("serial number reply is too short".into());
+    }
+    Ok(payload[..4].iter().rev().map(|b| format!("{:02X}", b)).collect())
+}
+
+/// Enttec DMX USB Pro compatible widget: DMX goes out as framed "Send DMX Packet"
+/// messages and the widget generates break and timing itself.
+pub struct EnttecProTransport {
+    port_name: String,
+    port: Option<Box<dyn SerialPort>>,
+}
+
+impl EnttecProTransport {
+    pub fn new(port_name: &str) -> Self {
+        EnttecProTransport {
+            port_name: port_name.to_string(),
+            port: None,
+        }
+    }
+
+    /// Wraps a port that is already open.
+    pub fn from_port(port: Box<dyn SerialPort>) -> Self {
+        EnttecProTransport {
+            port_name: port.name().unwrap_or_default(),
+            port: Some(port),
+        }
+    }
+
+    fn port(&mut self) -> Result<&mut Box<dyn SerialPort>, Box<dyn Error>> {
+        Ok(self.port.as_mut().ok_or("DMX widget is not open")?)
+    }
+
+    /// Sends a request and waits for the reply carrying the same label.
+    fn request(&mut self, label: u8, payload: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        let port = self.port()?;
+        port.write_all(&encode_message(label, payload))?;
+        loop {
+            let (reply_label, reply) = read_message(port)?;
+            if reply_label == label {
+                return Ok(reply);
+            }
+        }
+    }
+
+    pub fn widget_parameters(&mut self) -> Result<WidgetParameters, Box<dyn Error>> {
+        // Payload is the size of the user configuration block to return; we want none.
+        let reply = self.request(LABEL_GET_WIDGET_PARAMETERS, &[0, 0])?;
+        WidgetParameters::from_payload(&reply)
+    }
+
+    pub fn serial_number(&mut self) -> Result<String, Box<dyn Error>> {
+        let reply = self.request(LABEL_GET_SERIAL_NUMBER, &[])?;
+        decode_serial_number(&reply)
+    }
+}
+
+impl DmxTransport for EnttecProTransport {
+    fn open(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.port.is_none() {
+            let port = serialport::new(&self.port_name, WIDGET_BAUD_RATE)
+                .timeout(Duration::from_millis(200))
+                .open()?;
+            self.port = Some(port);
+        }
+        Ok(())
+    }
+
+    fn send_universe(&mut self, slots: &[u8]) -> Result<(), Box<dyn Error>> {
+        let mut payload: Vec<u8> = Vec::with_capacity(slots.len().max(MIN_DMX_SLOTS) + 1);
+        payload.push(0x00); // Start code
+        payload.extend_from_slice(&slots[..slots.len().min(DMX_FRAME_SIZE)]);
+        if payload.len() < MIN_DMX_SLOTS + 1 {
+            payload.resize(MIN_DMX_SLOTS + 1, 0);
+        }
+
+        self.port()?.write_all(&encode_message(LABEL_SEND_DMX_PACKET, &payload))?;
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(mut port) = self.port.take() {
+            port.flush()?;
+        }
+        Ok(())
+    }
+
+    fn capabilities(&self) -> TransportCapabilities {
+        TransportCapabilities {
+            max_slots: DMX_FRAME_SIZE,
+            generates_break: false,
+            networked: false,
+        }
+    }
+
+    /// Programs the widget's own break and MAB; it then sends as fast as it can
+    /// and the controller paces frames.
+    fn set_timing(&mut self, timing: &DmxTiming) -> Result<(), Box<dyn Error>> {
+        let message = encode_message(LABEL_SET_WIDGET_PARAMETERS, &timing_payload(timing, 0));
+        self.port()?.write_all(&message)?;
+        Ok(())
+    }
+}
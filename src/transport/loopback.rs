@@ -0,0 +1,219 @@
+//! Pseudo-terminal loopback adapter for end-to-end serial tests (Unix only).
+//!
+//! [`PtyLoopback`] opens a PTY pair; the slave's path goes wherever a COM port
+//! name would (`DmxController::new`, a binary's command line) and a background
+//! thread on the master side decodes what arrives into frames. PTYs carry no
+//! break, so Open DMX frames are split by length, one start code plus `slots`.
+//! Port enumeration does not list PTYs; pass the path explicitly.
+
+use serialport::{SerialPort, TTYPort};
+use std::error::Error;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use super::enttec::{
+    encode_message, END_OF_MESSAGE, LABEL_GET_SERIAL_NUMBER, LABEL_GET_WIDGET_PARAMETERS,
+    LABEL_SEND_DMX_PACKET, LABEL_SET_WIDGET_PARAMETERS, START_OF_MESSAGE,
+};
+use crate::dmx::DMX_FRAME_SIZE;
+
+/// What the fake adapter on the master side pretends to be.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoopbackAdapter {
+    /// Raw Open DMX cable carrying frames of one start code and `slots` slots.
+    OpenDmx { slots: usize },
+    /// Enttec DMX USB Pro widget answering serial number and parameter requests.
+    EnttecPro { serial_number: u32 },
+}
+
+/// Splits an Open DMX byte stream into frames of start code plus `slots` slots.
+pub struct FrameDecoder {
+    frame_len: usize,
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn new(slots: usize) -> Self {
+        FrameDecoder { frame_len: slots + 1, buffer: Vec::new() }
+    }
+
+    /// Adds received bytes and returns every frame they complete.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        self.buffer.extend_from_slice(bytes);
+        let complete = self.buffer.len() / self.frame_len * self.frame_len;
+        let frames = self.buffer[..complete].chunks(self.frame_len).map(<[u8]>::to_vec).collect();
+        self.buffer.drain(..complete);
+        frames
+    }
+}
+
+// Splits one widget message off the front of `buffer`, skipping noise before
+// the start delimiter. `None` until a whole message has arrived.
+fn take_message(buffer: &mut Vec<u8>) -> Option<Result<(u8, Vec<u8>), String>> {
+    let start = buffer.iter().position(|&b| b == START_OF_MESSAGE).unwrap_or(buffer.len());
+    buffer.drain(..start);
+    if buffer.len() < 4 {
+        return None;
+    }
+    let len = buffer[2] as usize | (buffer[3] as usize) << 8;
+    if len > DMX_FRAME_SIZE + 1 {
+        buffer.remove(0);
+        return Some(Err(format!("widget message length {} is too long", len)));
+    }
+    if buffer.len() < len + 5 {
+        return None;
+    }
+    let message: Vec<u8> = buffer.drain(..len + 5).collect();
+    if message[len + 4] != END_OF_MESSAGE {
+        return Some(Err("widget message is missing the end delimiter".to_string()));
+    }
+    Some(Ok((message[1], message[4..len + 4].to_vec())))
+}
+
+// Serial numbers go out as BCD, least significant byte first.
+fn serial_number_payload(serial_number: u32) -> Vec<u8> {
+    let digits = format!("{:08}", serial_number % 100_000_000);
+    let bcd: Vec<u8> = digits
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| (pair[0] - b'0') << 4 | (pair[1] - b'0'))
+        .collect();
+    bcd.into_iter().rev().collect()
+}
+
+struct Shared {
+    running: AtomicBool,
+    /// Last "Set Widget Parameters" payload, for Enttec emulation.
+    widget_parameters: Mutex<Vec<u8>>,
+    last_error: Mutex<Option<String>>,
+}
+
+/// A PTY pair whose master side decodes DMX sent to the slave.
+pub struct PtyLoopback {
+    slave_path: String,
+    // Held open so the master doesn't see a hang-up between clients.
+    _slave: TTYPort,
+    frames: Receiver<Vec<u8>>,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PtyLoopback {
+    pub fn open(adapter: LoopbackAdapter) -> Result<Self, Box<dyn Error>> {
+        let (master, slave) = TTYPort::pair()?;
+        let slave_path = slave.name().ok_or("PTY slave has no path")?;
+        let shared = Arc::new(Shared {
+            running: AtomicBool::new(true),
+            widget_parameters: Mutex::new(vec![0, 0, 9, 1, 40]),
+            last_error: Mutex::new(None),
+        });
+        let (sender, frames) = mpsc::channel();
+
+        let thread_shared = shared.clone();
+        let thread = thread::Builder::new()
+            .name("pty-loopback".to_string())
+            .spawn(move || {
+                if let Err(e) = run_master(master, adapter, sender, &thread_shared) {
+                    *thread_shared.last_error.lock().unwrap() = Some(e.to_string());
+                }
+            })?;
+
+        Ok(PtyLoopback { slave_path, _slave: slave, frames, shared, thread: Some(thread) })
+    }
+
+    /// Open DMX cable expecting full 512-slot frames.
+    pub fn open_dmx() -> Result<Self, Box<dyn Error>> {
+        Self::open(LoopbackAdapter::OpenDmx { slots: DMX_FRAME_SIZE })
+    }
+
+    /// Path of the slave side, to open like any serial port (e.g. `/dev/pts/3`).
+    pub fn port_name(&self) -> &str {
+        &self.slave_path
+    }
+
+    /// Waits up to `timeout` for the next frame: start code followed by slots.
+    pub fn recv_frame(&self, timeout: Duration) -> Option<Vec<u8>> {
+        self.frames.recv_timeout(timeout).ok()
+    }
+
+    /// Frames received so far and not yet taken.
+    pub fn drain_frames(&self) -> Vec<Vec<u8>> {
+        self.frames.try_iter().collect()
+    }
+
+    /// Last "Set Widget Parameters" payload an Enttec client sent.
+    pub fn widget_parameters(&self) -> Vec<u8> {
+        self.shared.widget_parameters.lock().unwrap().clone()
+    }
+
+    /// Why the master side stopped decoding, if it failed.
+    pub fn last_error(&self) -> Option<String> {
+        self.shared.last_error.lock().unwrap().clone()
+    }
+}
+
+impl Drop for PtyLoopback {
+    fn drop(&mut self) {
+        self.shared.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run_master(
+    mut master: TTYPort,
+    adapter: LoopbackAdapter,
+    frames: Sender<Vec<u8>>,
+    shared: &Shared,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    master.set_timeout(Duration::from_millis(20))?;
+    let mut decoder = match adapter {
+        LoopbackAdapter::OpenDmx { slots } => Some(FrameDecoder::new(slots)),
+        LoopbackAdapter::EnttecPro { .. } => None,
+    };
+    let mut pending = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    while shared.running.load(Ordering::SeqCst) {
+        let count = match master.read(&mut chunk) {
+            Ok(count) => count,
+            Err(e) if e.kind() == io::ErrorKind::TimedOut || e.kind() == io::ErrorKind::WouldBlock => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        if let Some(decoder) = decoder.as_mut() {
+            for frame in decoder.push(&chunk[..count]) {
+                let _ = frames.send(frame);
+            }
+            continue;
+        }
+
+        let LoopbackAdapter::EnttecPro { serial_number } = adapter else { continue };
+        pending.extend_from_slice(&chunk[..count]);
+        while let Some(message) = take_message(&mut pending) {
+            let (label, payload) = message?;
+            match label {
+                LABEL_SEND_DMX_PACKET => {
+                    let _ = frames.send(payload);
+                }
+                LABEL_SET_WIDGET_PARAMETERS => *shared.widget_parameters.lock().unwrap() = payload,
+                LABEL_GET_WIDGET_PARAMETERS => {
+                    let mut reply = vec![0x44, 0x01]; // Firmware 1.68
+                    reply.extend_from_slice(shared.widget_parameters.lock().unwrap().get(2..).unwrap_or_default());
+                    master.write_all(&encode_message(LABEL_GET_WIDGET_PARAMETERS, &reply))?;
+                }
+                LABEL_GET_SERIAL_NUMBER => {
+                    let reply = serial_number_payload(serial_number);
+                    master.write_all(&encode_message(LABEL_GET_SERIAL_NUMBER, &reply))?;
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,345 @@
+//! In-memory serial port for running the full send path without an adapter.
+//!
+//! A [`VirtualPort`] stands in for the USB serial device under
+//! [`OpenDmxTransport`] or [`EnttecProTransport`] and logs every break and
+//! write with a timestamp; a [`PortRecording`] turns that log back into frames.
+
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use super::{EnttecProTransport, OpenDmxTransport};
+use crate::dmx::{DmxTiming, DMX_BAUD_RATE};
+
+/// Something the transport did to the line.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PortEvent {
+    SetBreak,
+    ClearBreak,
+    Write(Vec<u8>),
+}
+
+#[derive(Default)]
+struct PortLog {
+    events: Vec<(Instant, PortEvent)>,
+    input: VecDeque<u8>,
+}
+
+/// A serial port that records instead of transmitting. Clones share the log.
+#[derive(Clone)]
+pub struct VirtualPort {
+    name: String,
+    log: Arc<Mutex<PortLog>>,
+    baud_rate: u32,
+    timeout: Duration,
+}
+
+impl VirtualPort {
+    pub fn new(name: &str) -> Self {
+        VirtualPort {
+            name: name.to_string(),
+            log: Arc::new(Mutex::new(PortLog::default())),
+            baud_rate: DMX_BAUD_RATE,
+            timeout: Duration::from_millis(10),
+        }
+    }
+
+    /// Handle for inspecting what has been sent, valid after the port is moved into a transport.
+    pub fn recording(&self) -> PortRecording {
+        PortRecording { log: self.log.clone() }
+    }
+
+    /// Queues bytes for the transport to read, e.g. a widget's reply.
+    pub fn push_input(&self, bytes: &[u8]) {
+        self.lock().input.extend(bytes);
+    }
+
+    /// An Open DMX transport writing to this port.
+    pub fn open_dmx(&self) -> OpenDmxTransport {
+        OpenDmxTransport::from_port(Box::new(self.clone()))
+    }
+
+    /// An Enttec DMX USB Pro transport writing to this port.
+    pub fn enttec_pro(&self) -> EnttecProTransport {
+        EnttecProTransport::from_port(Box::new(self.clone()))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PortLog> {
+        self.log.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn record(&self, event: PortEvent) {
+        self.lock().events.push((Instant::now(), event));
+    }
+}
+
+impl Read for VirtualPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut log = self.lock();
+        if log.input.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "no input queued on virtual port"));
+        }
+        let count = buf.len().min(log.input.len());
+        for (slot, byte) in buf.iter_mut().zip(log.input.drain(..count)) {
+            *slot = byte;
+        }
+        Ok(count)
+    }
+}
+
+impl Write for VirtualPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.record(PortEvent::Write(buf.to_vec()));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SerialPort for VirtualPort {
+    fn name(&self) -> Option<String> {
+        Some(self.name.clone())
+    }
+
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(self.baud_rate)
+    }
+
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(DataBits::Eight)
+    }
+
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(FlowControl::None)
+    }
+
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(Parity::None)
+    }
+
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(StopBits::Two)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> serialport::Result<()> {
+        self.baud_rate = baud_rate;
+        Ok(())
+    }
+
+    fn set_data_bits(&mut self, _data_bits: DataBits) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_flow_control(&mut self, _flow_control: FlowControl) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_parity(&mut self, _parity: Parity) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_stop_bits(&mut self, _stop_bits: StopBits) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        Ok(self.lock().input.len() as u32)
+    }
+
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        if matches!(buffer_to_clear, ClearBuffer::Input | ClearBuffer::All) {
+            self.lock().input.clear();
+        }
+        Ok(())
+    }
+
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Ok(Box::new(self.clone()))
+    }
+
+    fn set_break(&self) -> serialport::Result<()> {
+        self.record(PortEvent::SetBreak);
+        Ok(())
+    }
+
+    fn clear_break(&self) -> serialport::Result<()> {
+        self.record(PortEvent::ClearBreak);
+        Ok(())
+    }
+}
+
+/// One frame as it went down the line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedFrame {
+    /// When the break started, or the first byte was written for break-less (widget) frames.
+    pub at: Instant,
+    /// How long the line was held in break; `None` if the host sent no break.
+    pub break_time: Option<Duration>,
+    /// From the end of the break to the first byte written.
+    pub mark_after_break: Option<Duration>,
+    /// Everything written for the frame: start code and slots, or a widget message.
+    pub bytes: Vec<u8>,
+}
+
+impl RecordedFrame {
+    pub fn start_code(&self) -> Option<u8> {
+        self.bytes.first().copied()
+    }
+
+    /// Slot data after the start code.
+    pub fn slots(&self) -> &[u8] {
+        self.bytes.get(1..).unwrap_or_default()
+    }
+
+    /// 1-based DMX channel, as on a desk.
+    pub fn channel(&self, channel: usize) -> Option<u8> {
+        channel.checked_sub(1).and_then(|index| self.slots().get(index).copied())
+    }
+}
+
+/// Shared view of a [`VirtualPort`]'s log.
+#[derive(Clone)]
+pub struct PortRecording {
+    log: Arc<Mutex<PortLog>>,
+}
+
+impl PortRecording {
+    pub fn events(&self) -> Vec<(Instant, PortEvent)> {
+        self.log.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).events.clone()
+    }
+
+    pub fn clear(&self) {
+        self.log.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).events.clear();
+    }
+
+    /// Groups the log into frames: a break starts a new frame and collects the
+    /// writes up to the next break. Writes with no break before them (widgets)
+    /// are a frame each.
+    pub fn frames(&self) -> Vec<RecordedFrame> {
+        let mut frames: Vec<RecordedFrame> = Vec::new();
+        let mut in_break: Option<Instant> = None;
+        let mut after_break: Option<(Instant, Duration)> = None;
+        let mut current: Option<RecordedFrame> = None;
+
+        for (at, event) in self.events() {
+            match event {
+                PortEvent::SetBreak => {
+                    frames.extend(current.take());
+                    after_break = None;
+                    in_break = Some(at);
+                }
+                PortEvent::ClearBreak => {
+                    if let Some(start) = in_break.take() {
+                        after_break = Some((at, at - start));
+                        current = Some(RecordedFrame {
+                            at: start,
+                            break_time: Some(at - start),
+                            mark_after_break: None,
+                            bytes: Vec::new(),
+                        });
+                    }
+                }
+                PortEvent::Write(bytes) => match current.as_mut() {
+                    Some(frame) if frame.break_time.is_some() => {
+                        if frame.mark_after_break.is_none()
+                            && let Some((cleared, _)) = after_break
+                        {
+                            frame.mark_after_break = Some(at - cleared);
+                        }
+                        frame.bytes.extend_from_slice(&bytes);
+                    }
+                    _ => {
+                        frames.extend(current.take());
+                        current = Some(RecordedFrame { at, break_time: None, mark_after_break: None, bytes });
+                    }
+                },
+            }
+        }
+        frames.extend(current);
+        frames
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames().len()
+    }
+
+    /// Frame `index` (0-based), panicking with the number recorded if it is missing.
+    pub fn frame(&self, index: usize) -> RecordedFrame {
+        let frames = self.frames();
+        match frames.get(index) {
+            Some(frame) => frame.clone(),
+            None => panic!("expected frame {}, only {} recorded", index, frames.len()),
+        }
+    }
+
+    pub fn last_frame(&self) -> Option<RecordedFrame> {
+        self.frames().pop()
+    }
+
+    /// Time from the start of each frame to the start of the next.
+    pub fn gaps(&self) -> Vec<Duration> {
+        self.frames().windows(2).map(|pair| pair[1].at - pair[0].at).collect()
+    }
+
+    /// Panics unless 1-based `channel` was `value` in frame `index`.
+    pub fn assert_channel(&self, index: usize, channel: usize, value: u8) {
+        let actual = self.frame(index).channel(channel);
+        assert_eq!(actual, Some(value), "channel {} in frame {}", channel, index);
+    }
+
+    /// Panics unless every frame had at least the break, mark-after-break and
+    /// break-to-break times `timing` asks for.
+    pub fn assert_timing(&self, timing: &DmxTiming) {
+        let frames = self.frames();
+        for (index, frame) in frames.iter().enumerate() {
+            let break_time = frame.break_time.unwrap_or_else(|| panic!("frame {} has no break", index));
+            assert!(break_time >= timing.break_time, "frame {}: break {:?} < {:?}", index, break_time, timing.break_time);
+            let mab = frame.mark_after_break.unwrap_or_default();
+            assert!(mab >= timing.mark_after_break, "frame {}: MAB {:?} < {:?}", index, mab, timing.mark_after_break);
+        }
+        for (index, gap) in self.gaps().iter().enumerate() {
+            assert!(*gap >= timing.frame_period(), "frames {}-{}: {:?} < {:?}", index, index + 1, gap, timing.frame_period());
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::error::Error;
+
+use crate::dmx::DmxTiming;
+
+pub mod artnet;
+pub mod discovery;
+pub mod enttec;
+#[cfg(unix)]
+pub mod loopback;
+pub mod mock;
+pub mod reconnect;
+pub mod sacn;
+pub mod selector;
+pub mod serial;
+
+pub use artnet::ArtNetTransport;
+pub use discovery::{discover_ports, DiscoveredPort, ProbeResult};
+pub use enttec::EnttecProTransport;
+pub use mock::{PortRecording, VirtualPort};
+pub use reconnect::ReconnectingTransport;
+pub use sacn::SacnTransport;
+pub use selector::{AdapterAliases, PortSelector};
+pub use serial::OpenDmxTransport;
+
+/// What a transport can and cannot do, so callers can adapt frame size and rate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TransportCapabilities {
+    /// Largest number of slots (excluding the start code) one frame can carry.
+    pub max_slots: usize,
+    /// Whether the host toggles the DMX break itself, as opposed to a widget or node doing it.
+    pub generates_break: bool,
+    /// Whether frames leave the machine over the network rather than a local adapter.
+    pub networked: bool,
+}
+
+/// A way of getting one DMX universe out of the machine.
+///
+/// `send_universe` receives the slot data only; the transport adds the start
+/// code, break or protocol header its medium needs.
+pub trait DmxTransport: Send {
+    fn open(&mut self) -> Result<(), Box<dyn Error>>;
+    fn send_universe(&mut self, slots: &[u8]) -> Result<(), Box<dyn Error>>;
+    fn close(&mut self) -> Result<(), Box<dyn Error>>;
+    fn capabilities(&self) -> TransportCapabilities;
+
+    /// Applies break and mark-after-break timing, for transports that control
+    /// them. The controller has already validated `timing`.
+    fn set_timing(&mut self, _timing: &DmxTiming) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+/// Kind of USB serial DMX adapter plugged in at a port.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SerialAdapter {
+    /// Raw FTDI-style cable: host sends break, start code and slots.
+    OpenDmx,
+    /// Enttec DMX USB Pro compatible widget speaking the framed message API.
+    EnttecPro,
+}
+
+impl SerialAdapter {
+    pub fn transport(self, port_name: &str) -> Box<dyn DmxTransport> {
+        match self {
+            SerialAdapter::OpenDmx => Box::new(OpenDmxTransport::new(port_name)),
+            SerialAdapter::EnttecPro => Box::new(EnttecProTransport::new(port_name)),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use super::selector::{AdapterAliases, PortSelector};
+use super::{DmxTransport, SerialAdapter, TransportCapabilities};
+use crate::dmx::{DmxTiming, DMX_FRAME_SIZE};
+
+/// Opens a fresh transport, e.g. by finding an adapter by serial number.
+pub type Connector = Box<dyn FnMut() -> Result<Box<dyn DmxTransport>, Box<dyn Error>> + Send>;
+
+pub const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Wraps a transport that may disappear, such as a USB adapter pulled mid-show.
+///
+/// A failed send drops the connection; later sends try to reconnect (at most
+/// once per retry interval), reapply the timing and carry on with the universe
+/// they were given, so a refreshing controller resumes where it left off.
+pub struct ReconnectingTransport {
+    connect: Connector,
+    inner: Option<Box<dyn DmxTransport>>,
+    capabilities: TransportCapabilities,
+    timing: Option<DmxTiming>,
+    retry_interval: Duration,
+    last_attempt: Option<Instant>,
+    reconnects: u64,
+}
+
+impl ReconnectingTransport {
+    pub fn new(connect: Connector) -> Self {
+        ReconnectingTransport {
+            connect,
+            inner: None,
+            capabilities: TransportCapabilities { max_slots: DMX_FRAME_SIZE, generates_break: true, networked: false },
+            timing: None,
+            retry_interval: DEFAULT_RETRY_INTERVAL,
+            last_attempt: None,
+            reconnects: 0,
+        }
+    }
+
+    /// Finds the adapter through `selector` on every (re)connect, so it may come
+    /// back on a different port. `adapter` overrides the type guessed from its descriptors.
+    pub fn serial(selector: PortSelector, aliases: AdapterAliases, adapter: Option<SerialAdapter>) -> Self {
+        Self::new(Box::new(move || {
+            let (port_name, detected) = selector.resolve(&aliases)?;
+            let adapter = adapter.or(detected).unwrap_or(SerialAdapter::OpenDmx);
+            Ok(adapter.transport(&port_name))
+        }))
+    }
+
+    pub fn with_retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// How many times the connection has been re-established after a failure.
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects
+    }
+
+    fn connect(&mut self) -> Result<(), Box<dyn Error>> {
+        self.last_attempt = Some(Instant::now());
+        let mut transport = (self.connect)()?;
+        transport.open()?;
+        if let Some(timing) = &self.timing {
+            transport.set_timing(timing)?;
+        }
+        self.capabilities = transport.capabilities();
+        self.inner = Some(transport);
+        Ok(())
+    }
+
+    fn disconnect(&mut self) {
+        if let Some(mut transport) = self.inner.take() {
+            let _ = transport.close();
+        }
+    }
+}
+
+impl DmxTransport for ReconnectingTransport {
+    fn open(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.inner.is_none() {
+            self.connect()?;
+        }
+        Ok(())
+    }
+
+    fn send_universe(&mut self, slots: &[u8]) -> Result<(), Box<dyn Error>> {
+        if self.inner.is_none() {
+            if self.last_attempt.is_some_and(|at| at.elapsed() < self.retry_interval) {
+                return Err("DMX adapter is disconnected".into());
+            }
+            self.connect().map_err(|e| format!("DMX adapter is disconnected: {}", e))?;
+            self.reconnects += 1;
+        }
+
+        let result = match self.inner.as_mut() {
+            Some(transport) => transport.send_universe(slots),
+            None => return Err("DMX adapter is disconnected".into()),
+        };
+        if result.is_err() {
+            self.disconnect();
+            self.last_attempt = Some(Instant::now());
+        }
+        result
+    }
+
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        match self.inner.take() {
+            Some(mut transport) => transport.close(),
+            None => Ok(()),
+        }
+    }
+
+    fn capabilities(&self) -> TransportCapabilities {
+        self.capabilities
+    }
+
+    fn set_timing(&mut self, timing: &DmxTiming) -> Result<(), Box<dyn Error>> {
+        self.timing = Some(*timing);
+        match self.inner.as_mut() {
+            Some(transport) => transport.set_timing(timing),
+            None => Ok(()),
+        }
+    }
+}
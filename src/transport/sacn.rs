@@ -0,0 +1,220 @@
+use std::error::Error;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+
+use super::{DmxTransport, TransportCapabilities};
+use crate::dmx::DMX_FRAME_SIZE;
+
+pub const SACN_PORT: u16 = 5568;
+pub const ACN_PACKET_IDENTIFIER: &[u8; 12] = b"ASC-E1.17\0\0\0";
+pub const VECTOR_ROOT_E131_DATA: u32 = 0x0000_0004;
+pub const VECTOR_E131_DATA_PACKET: u32 = 0x0000_0002;
+pub const VECTOR_DMP_SET_PROPERTY: u8 = 0x02;
+
+pub const DEFAULT_PRIORITY: u8 = 100;
+pub const MAX_PRIORITY: u8 = 200;
+
+pub const OPTION_PREVIEW_DATA: u8 = 0x80;
+pub const OPTION_STREAM_TERMINATED: u8 = 0x40;
+
+const ROOT_LAYER_OFFSET: usize = 16;
+const FRAMING_LAYER_OFFSET: usize = 38;
+const DMP_LAYER_OFFSET: usize = 115;
+const HEADER_SIZE: usize = 126;
+const SOURCE_NAME_SIZE: usize = 64;
+
+/// Multicast group a receiver joins for `universe`: 239.255.{hi}.{lo}.
+pub fn multicast_address(universe: u16) -> Ipv4Addr {
+    Ipv4Addr::new(239, 255, (universe >> 8) as u8, (universe & 0xFF) as u8)
+}
+
+/// Everything in an E1.31 data packet except the slot data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SacnHeader {
+    pub cid: [u8; 16],
+    pub source_name: String,
+    pub priority: u8,
+    pub sequence: u8,
+    pub options: u8,
+    pub universe: u16,
+}
+
+// PDU "flags and length": the high nibble is always 0x7, the low 12 bits the PDU length.
+fn flags_and_length(length: usize) -> [u8; 2] {
+    (0x7000 | (length as u16 & 0x0FFF)).to_be_bytes()
+}
+
+/// Builds an E1.31 data packet with root, framing and DMP layers; start code 0 is prepended.
+pub fn encode_data_packet(header: &SacnHeader, slots: &[u8]) -> Vec<u8> {
+    let slots = &slots[..slots.len().min(DMX_FRAME_SIZE)];
+    let total = HEADER_SIZE + slots.len();
+
+    let mut packet = Vec::with_capacity(total);
+    // Root layer
+    packet.extend_from_slice(&0x0010u16.to_be_bytes()); // Preamble size
+    packet.extend_from_slice(&0x0000u16.to_be_bytes()); // Post-amble size
+    packet.extend_from_slice(ACN_PACKET_IDENTIFIER);
+    packet.extend_from_slice(&flags_and_length(total - ROOT_LAYER_OFFSET));
+    packet.extend_from_slice(&VECTOR_ROOT_E131_DATA.to_be_bytes());
+    packet.extend_from_slice(&header.cid);
+
+    // Framing layer
+    packet.extend_from_slice(&flags_and_length(total - FRAMING_LAYER_OFFSET));
+    packet.extend_from_slice(&VECTOR_E131_DATA_PACKET.to_be_bytes());
+    let mut name = [0u8; SOURCE_NAME_SIZE];
+    let name_bytes = header.source_name.as_bytes();
+    let name_len = name_bytes.len().min(SOURCE_NAME_SIZE - 1); // Keep the null terminator
+    name[..name_len].copy_from_slice(&name_bytes[..name_len]);
+    packet.extend_from_slice(&name);
+    packet.push(header.priority.min(MAX_PRIORITY));
+    packet.extend_from_slice(&0u16.to_be_bytes()); // Synchronization address, unused
+    packet.push(header.sequence);
+    packet.push(header.options);
+    packet.extend_from_slice(&header.universe.to_be_bytes());
+
+    // DMP layer
+    packet.extend_from_slice(&flags_and_length(total - DMP_LAYER_OFFSET));
+    packet.push(VECTOR_DMP_SET_PROPERTY);
+    packet.push(0xA1); // Address type and data type
+    packet.extend_from_slice(&0x0000u16.to_be_bytes()); // First property address
+    packet.extend_from_slice(&0x0001u16.to_be_bytes()); // Address increment
+    packet.extend_from_slice(&((slots.len() + 1) as u16).to_be_bytes());
+    packet.push(0x00); // Start code
+    packet.extend_from_slice(slots);
+    packet
+}
+
+/// Parses the header of an E1.31 data packet, returning it with the slot data.
+pub fn decode_data_packet(packet: &[u8]) -> Result<(SacnHeader, &[u8]), Box<dyn Error>> {
+    if packet.len() < HEADER_SIZE || &packet[4..16] != ACN_PACKET_IDENTIFIER {
+        return Err("not an E1.31 packet".into());
+    }
+    if packet[18..22] != VECTOR_ROOT_E131_DATA.to_be_bytes()
+        || packet[40..44] != VECTOR_E131_DATA_PACKET.to_be_bytes()
+        || packet[117] != VECTOR_DMP_SET_PROPERTY
+    {
+        return Err("not an E1.31 data packet".into());
+    }
+
+    let mut cid = [0u8; 16];
+    cid.copy_from_slice(&packet[22..38]);
+    let name = &packet[44..44 + SOURCE_NAME_SIZE];
+    let name_len = name.iter().position(|&b| b == 0).unwrap_or(SOURCE_NAME_SIZE);
+    let count = u16::from_be_bytes([packet[123], packet[124]]) as usize;
+    if count == 0 || HEADER_SIZE - 1 + count > packet.len() {
+        return Err("E1.31 property value count does not match packet length".into());
+    }
+
+    let header = SacnHeader {
+        cid,
+        source_name: String::from_utf8_lossy(&name[..name_len]).into_owned(),
+        priority: packet[108],
+        sequence: packet[111],
+        options: packet[112],
+        universe: u16::from_be_bytes([packet[113], packet[114]]),
+    };
+    Ok((header, &packet[HEADER_SIZE..HEADER_SIZE - 1 + count]))
+}
+
+/// Streams universes as sACN (ANSI E1.31) data packets, multicast or unicast.
+pub struct SacnTransport {
+    target: SocketAddr,
+    socket: Option<UdpSocket>,
+    header: SacnHeader,
+    last_slots: Vec<u8>,
+}
+
+impl SacnTransport {
+    /// Sends to the standard multicast group for `universe` (1-63999).
+    pub fn multicast(universe: u16, source_name: &str) -> Result<Self, Box<dyn Error>> {
+        let target = SocketAddr::V4(SocketAddrV4::new(multicast_address(universe), SACN_PORT));
+        Self::unicast(target, universe, source_name)
+    }
+
+    /// Sends straight to one receiver, e.g. a node at `10.0.0.20:5568`.
+    pub fn unicast(target: SocketAddr, universe: u16, source_name: &str) -> Result<Self, Box<dyn Error>> {
+        if !(1..=63999).contains(&universe) {
+            return Err("sACN universe must be between 1 and 63999".into());
+        }
+        Ok(SacnTransport {
+            target,
+            socket: None,
+            header: SacnHeader {
+                cid: *uuid::Uuid::new_v4().as_bytes(),
+                source_name: source_name.to_string(),
+                priority: DEFAULT_PRIORITY,
+                sequence: 0,
+                options: 0,
+                universe,
+            },
+            last_slots: Vec::new(),
+        })
+    }
+
+    /// Uses a fixed component identifier, so receivers see the same source across restarts.
+    pub fn with_cid(mut self, cid: [u8; 16]) -> Self {
+        self.header.cid = cid;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: u8) -> Result<Self, Box<dyn Error>> {
+        if priority > MAX_PRIORITY {
+            return Err("sACN priority must be between 0 and 200".into());
+        }
+        self.header.priority = priority;
+        Ok(self)
+    }
+
+    pub fn cid(&self) -> [u8; 16] {
+        self.header.cid
+    }
+
+    fn send_packet(&mut self, options: u8) -> Result<(), Box<dyn Error>> {
+        self.header.sequence = self.header.sequence.wrapping_add(1);
+        self.header.options = options;
+        let packet = encode_data_packet(&self.header, &self.last_slots);
+        let socket = self.socket.as_ref().ok_or("sACN socket is not open")?;
+        socket.send_to(&packet, self.target)?;
+        Ok(())
+    }
+}
+
+impl DmxTransport for SacnTransport {
+    fn open(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.socket.is_none() {
+            self.socket = Some(UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?);
+        }
+        Ok(())
+    }
+
+    fn send_universe(&mut self, slots: &[u8]) -> Result<(), Box<dyn Error>> {
+        self.last_slots.clear();
+        self.last_slots.extend_from_slice(slots);
+        self.send_packet(0)
+    }
+
+    /// Announces the end of the stream (three packets with the Stream_Terminated
+    /// option, as E1.31 asks) so receivers release the universe straight away.
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.socket.is_some() {
+            for _ in 0..3 {
+                self.send_packet(OPTION_STREAM_TERMINATED)?;
+            }
+            self.socket = None;
+        }
+        Ok(())
+    }
+
+    fn capabilities(&self) -> TransportCapabilities {
+        TransportCapabilities {
+            max_slots: DMX_FRAME_SIZE,
+            generates_break: false,
+            networked: true,
+        }
+    }
+}
+
+impl Drop for SacnTransport {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
@@ -0,0 +1,160 @@
+//! Naming an adapter by something that survives a reboot: its USB serial
+//! number or an alias, rather than `COM4` or `/dev/ttyUSB0`.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use super::discovery::{discover_ports, DiscoveredPort};
+use super::SerialAdapter;
+
+/// Which adapter to use. Parses from `COM4`, `/dev/ttyUSB0`, `serial:EN123456` or `alias:stage-left`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PortSelector {
+    Name(String),
+    SerialNumber(String),
+    Alias(String),
+}
+
+impl FromStr for PortSelector {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let selector = if let Some(serial) = s.strip_prefix("serial:") {
+            PortSelector::SerialNumber(serial.to_string())
+        } else if let Some(alias) = s.strip_prefix("alias:") {
+            PortSelector::Alias(alias.to_string())
+        } else {
+            PortSelector::Name(s.to_string())
+        };
+        match &selector {
+            PortSelector::Name(value) | PortSelector::SerialNumber(value) | PortSelector::Alias(value)
+                if value.is_empty() =>
+            {
+                Err(format!("empty port selector '{}'", s).into())
+            }
+            _ => Ok(selector),
+        }
+    }
+}
+
+impl fmt::Display for PortSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortSelector::Name(name) => write!(f, "{}", name),
+            PortSelector::SerialNumber(serial) => write!(f, "serial:{}", serial),
+            PortSelector::Alias(alias) => write!(f, "alias:{}", alias),
+        }
+    }
+}
+
+impl PortSelector {
+    /// Picks the matching port out of `ports`. Aliases are looked up in `aliases` first.
+    pub fn find<'a>(
+        &self,
+        ports: &'a [DiscoveredPort],
+        aliases: &AdapterAliases,
+    ) -> Result<Option<&'a DiscoveredPort>, Box<dyn Error>> {
+        Ok(match self {
+            PortSelector::Name(name) => ports.iter().find(|port| &port.port_name == name),
+            PortSelector::SerialNumber(serial) => ports.iter().find(|port| {
+                port.serial_number.as_deref().is_some_and(|s| s.eq_ignore_ascii_case(serial))
+            }),
+            PortSelector::Alias(alias) => aliases.get(alias)?.find(ports, aliases)?,
+        })
+    }
+
+    /// Finds the port on this machine now, with the adapter type its descriptors
+    /// suggest. A named port that enumeration doesn't list (PTYs, some
+    /// virtual ports) is still returned, with no adapter type.
+    pub fn resolve(&self, aliases: &AdapterAliases) -> Result<(String, Option<SerialAdapter>), Box<dyn Error>> {
+        let ports = discover_ports().unwrap_or_default();
+        if let Some(port) = self.find(&ports, aliases)? {
+            return Ok((port.port_name.clone(), port.likely_adapter()));
+        }
+        match self {
+            PortSelector::Name(name) => Ok((name.clone(), None)),
+            PortSelector::Alias(alias) => match aliases.get(alias)? {
+                PortSelector::Name(name) => Ok((name, None)),
+                target => Err(format!("no adapter connected for '{}' ({})", self, target).into()),
+            },
+            PortSelector::SerialNumber(_) => Err(format!("no adapter connected with {}", self).into()),
+        }
+    }
+}
+
+/// User-assigned adapter names, stored as a JSON object of alias to selector:
+/// `{ "stage-left": "serial:EN123456" }`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AdapterAliases {
+    aliases: BTreeMap<String, String>,
+}
+
+impl AdapterAliases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `$LASERPORT_ADAPTERS`, else `adapters.json` in the user's laserport config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os("LASERPORT_ADAPTERS") {
+            return Some(PathBuf::from(path));
+        }
+        let config = env::var_os("APPDATA")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("XDG_CONFIG_HOME").map(PathBuf::from))
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config.join("laserport").join("adapters.json"))
+    }
+
+    /// Loads the default alias file; no file means no aliases.
+    pub fn load_default() -> Result<Self, Box<dyn Error>> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(path),
+            _ => Ok(Self::new()),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let aliases: BTreeMap<String, String> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Ok(AdapterAliases { aliases })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&self.aliases)?)?;
+        Ok(())
+    }
+
+    /// Names `target`, which may not itself be an alias.
+    pub fn insert(&mut self, alias: &str, target: &PortSelector) -> Result<(), Box<dyn Error>> {
+        if let PortSelector::Alias(_) = target {
+            return Err("an alias cannot point at another alias".into());
+        }
+        self.aliases.insert(alias.to_string(), target.to_string());
+        Ok(())
+    }
+
+    pub fn remove(&mut self, alias: &str) -> bool {
+        self.aliases.remove(alias).is_some()
+    }
+
+    pub fn get(&self, alias: &str) -> Result<PortSelector, Box<dyn Error>> {
+        let target = self.aliases.get(alias).ok_or_else(|| format!("unknown adapter alias '{}'", alias))?;
+        match target.parse()? {
+            PortSelector::Alias(_) => Err(format!("alias '{}' points at another alias", alias).into()),
+            selector => Ok(selector),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases.iter().map(|(alias, target)| (alias.as_str(), target.as_str()))
+    }
+}
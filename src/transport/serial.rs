@@ -0,0 +1,87 @@
+use serialport::{DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::error::Error;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+use super::{DmxTransport, TransportCapabilities};
+use crate::dmx::{DmxTiming, DMX_BAUD_RATE, DMX_FRAME_SIZE};
+
+/// "Open DMX" style output: a plain USB-to-serial adapter (e.g. FT232R) where the
+/// host toggles the break itself and writes the start code and slots at 250 kbaud.
+pub struct OpenDmxTransport {
+    port_name: String,
+    port: Option<Box<dyn SerialPort>>,
+    timing: DmxTiming,
+}
+
+impl OpenDmxTransport {
+    pub fn new(port_name: &str) -> Self {
+        OpenDmxTransport {
+            port_name: port_name.to_string(),
+            port: None,
+            timing: DmxTiming::default(),
+        }
+    }
+
+    /// Wraps a port that is already open and configured for DMX.
+    pub fn from_port(port: Box<dyn SerialPort>) -> Self {
+        OpenDmxTransport {
+            port_name: port.name().unwrap_or_default(),
+            port: Some(port),
+            timing: DmxTiming::default(),
+        }
+    }
+}
+
+impl DmxTransport for OpenDmxTransport {
+    fn open(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.port.is_none() {
+            let port = serialport::new(&self.port_name, DMX_BAUD_RATE)
+                .data_bits(DataBits::Eight)
+                .flow_control(FlowControl::None)
+                .parity(Parity::None)
+                .stop_bits(StopBits::Two)
+                .timeout(Duration::from_millis(10))
+                .open()?;
+            self.port = Some(port);
+        }
+        Ok(())
+    }
+
+    fn send_universe(&mut self, slots: &[u8]) -> Result<(), Box<dyn Error>> {
+        let port = self.port.as_mut().ok_or("DMX port is not open")?;
+
+        port.set_break()?;
+        thread::sleep(self.timing.break_time);
+        port.clear_break()?;
+        thread::sleep(self.timing.mark_after_break);
+
+        let mut frame: Vec<u8> = Vec::with_capacity(slots.len() + 1);
+        frame.push(0x00); // Start code
+        frame.extend_from_slice(slots);
+
+        port.write_all(&frame)?;
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(mut port) = self.port.take() {
+            port.flush()?;
+        }
+        Ok(())
+    }
+
+    fn capabilities(&self) -> TransportCapabilities {
+        TransportCapabilities {
+            max_slots: DMX_FRAME_SIZE,
+            generates_break: true,
+            networked: false,
+        }
+    }
+
+    fn set_timing(&mut self, timing: &DmxTiming) -> Result<(), Box<dyn Error>> {
+        self.timing = *timing;
+        Ok(())
+    }
+}
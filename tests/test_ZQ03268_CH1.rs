@@ -1,13 +1,30 @@
-use laserport::dmxcharts::ZQ03268;
+use laserport::dmx::DmxController;
+use laserport::dmxcharts::ZQ03268::{self, LaserState, MainSwitch};
+use laserport::transport::VirtualPort;
 
 #[test]
 fn test_ch1() {
-    println!("test ZQ03268 CH 1");
-    ZQ03268::test();
+    let mut state = LaserState::new();
+    assert_eq!(state.to_channels()[0], 0);
 
+    state.ch1 = MainSwitch::On;
+    let dmx = state.to_dmx_state();
+    assert_eq!(dmx.channels.len(), ZQ03268::CHANNEL_COUNT);
+    assert_eq!(dmx.get_channel(1), Some(255));
+    assert_eq!(ZQ03268::CHANNEL_NAMES[0], "Main switch");
+}
 
-    
-
+#[test]
+fn test_ch1_on_the_wire() {
+    let port = VirtualPort::new("virtual0");
+    let recording = port.recording();
+    let mut controller = DmxController::with_transport(Box::new(port.open_dmx()), 1).unwrap();
 
+    let mut state = LaserState::new();
+    controller.send(&state.to_dmx_state()).unwrap();
+    state.ch1 = MainSwitch::On;
+    controller.send(&state.to_dmx_state()).unwrap();
 
-}
\ No newline at end of file
+    recording.assert_channel(0, 1, 0);
+    recording.assert_channel(1, 1, 255);
+}
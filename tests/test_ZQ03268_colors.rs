@@ -0,0 +1,37 @@
+use laserport::dmxcharts::ZQ03268::{ColorMode, FIXED_COLORS};
+
+#[test]
+fn test_fixed_color_bands() {
+    // Bands are contiguous, cover 0-69, and each encodes and decodes inside its own band.
+    let mut next = 0u8;
+    for color in FIXED_COLORS.iter() {
+        assert_eq!(color.min, next);
+        for value in color.min..=color.max {
+            assert_eq!(ColorMode::from_u8(value), color.mode, "value {}", value);
+        }
+        assert!((color.min..=color.max).contains(&color.mode.to_u8()));
+        assert_eq!(color.mode.fixed_color(), Some(color));
+        next = color.max + 1;
+    }
+    assert_eq!(next, 70);
+    assert_eq!(ColorMode::from_u8(70), ColorMode::OverallChange);
+    assert_eq!(ColorMode::Rainbow.fixed_color(), None);
+}
+
+#[test]
+fn test_fixed_by_name() {
+    assert_eq!(ColorMode::fixed_by_name("red"), Some(ColorMode::FixedRed));
+    assert_eq!(ColorMode::fixed_by_name("Cyan"), Some(ColorMode::FixedCyan));
+    assert_eq!(ColorMode::fixed_by_name("orange"), None);
+}
+
+#[test]
+fn test_nearest_fixed() {
+    assert_eq!(ColorMode::nearest_fixed((250, 10, 5)), ColorMode::FixedRed);
+    assert_eq!(ColorMode::nearest_fixed((80, 0, 0)), ColorMode::FixedRed);
+    assert_eq!(ColorMode::nearest_fixed((255, 200, 0)), ColorMode::FixedYellow);
+    assert_eq!(ColorMode::nearest_fixed((128, 0, 255)), ColorMode::FixedPurple);
+    assert_eq!(ColorMode::nearest_fixed((0, 180, 200)), ColorMode::FixedCyan);
+    assert_eq!(ColorMode::nearest_fixed((240, 240, 250)), ColorMode::FixedWhite);
+    assert_eq!(ColorMode::nearest_fixed((0, 0, 0)), ColorMode::FixedWhite);
+}
@@ -0,0 +1,78 @@
+use laserport::dmx::DmxState;
+use laserport::dmxcharts::ZQ03268::*;
+
+// Every byte must decode to a variant that re-encodes into the same range.
+macro_rules! assert_stable {
+    ($ty:ident) => {
+        for value in 0..=255u8 {
+            let decoded = $ty::from_u8(value);
+            assert_eq!($ty::from_u8(decoded.to_u8()), decoded, "{} value {}", stringify!($ty), value);
+        }
+    };
+}
+
+#[test]
+fn test_every_value_round_trips() {
+    assert_stable!(MainSwitch);
+    assert_stable!(ColorMode);
+    assert_stable!(ColorFlow);
+    assert_stable!(GraphicsGroup);
+    assert_stable!(DynamicEffect);
+    assert_stable!(AutoScaling);
+    assert_stable!(RotationCenter);
+    assert_stable!(FlipHorizontal);
+    assert_stable!(MovementHorizontal);
+    assert_stable!(WavesX);
+    assert_stable!(GradualDrawing);
+}
+
+#[test]
+fn test_range_boundaries() {
+    assert_eq!(MainSwitch::from_u8(9), MainSwitch::Off);
+    assert_eq!(MainSwitch::from_u8(10), MainSwitch::On);
+    assert_eq!(ColorMode::from_u8(92), ColorMode::Rainbow);
+    assert_eq!(ColorMode::from_u8(93), ColorMode::Seg2);
+    assert_eq!(ColorMode::from_u8(254), ColorMode::Gradient);
+    assert_eq!(ColorFlow::from_u8(127), ColorFlow::Forward(117));
+    assert_eq!(ColorFlow::from_u8(128), ColorFlow::Reverse(0));
+    assert_eq!(GraphicsGroup::from_u8(124), GraphicsGroup::Static5);
+    assert_eq!(DynamicEffect::from_u8(206), DynamicEffect::Single(102));
+    assert_eq!(DynamicEffect::from_u8(207), DynamicEffect::LineRandom);
+    assert_eq!(AutoScaling::from_u8(55), AutoScaling::SmallToLarge(39));
+    assert_eq!(RotationCenter::from_u8(192), RotationCenter::ReverseSpeed(0));
+    assert_eq!(WavesX::from_u8(255), WavesX::AmpSpeed(7));
+    assert_eq!(GradualDrawing::from_u8(153), GradualDrawing::AutoClockwise(25));
+}
+
+#[test]
+fn test_laser_state_round_trip() {
+    let mut state = LaserState::new();
+    state.ch1 = MainSwitch::On;
+    state.ch2 = ColorMode::Seg8;
+    state.ch3 = ColorFlow::Reverse(40);
+    state.ch4 = GraphicsGroup::Animation2;
+    state.ch5 = 17;
+    state.ch6 = DynamicEffect::Single(12);
+    state.ch7 = 200;
+    state.ch9 = AutoScaling::LargeToSmall(5);
+    state.ch10 = RotationCenter::ForwardSpeed(30);
+    state.ch11 = FlipHorizontal::Speed(3);
+    state.ch13 = MovementHorizontal::CircularSpeed(90);
+    state.ch15 = WavesX::AmpSpeed(4);
+    state.ch16 = GradualDrawing::AutoCounter(8);
+
+    assert_eq!(LaserState::from_channels(&state.to_channels()), state);
+}
+
+#[test]
+fn test_from_dmx_state_at_address() {
+    let mut state = LaserState::new();
+    state.ch1 = MainSwitch::On;
+    state.ch7 = 99;
+
+    let mut universe = DmxState::new(512);
+    universe.channels[99..115].copy_from_slice(&state.to_channels());
+    assert_eq!(LaserState::from_dmx_state(&universe, 100), Some(state));
+    assert_eq!(LaserState::from_dmx_state(&universe, 0), None);
+    assert_eq!(LaserState::from_dmx_state(&universe, 500), None);
+}
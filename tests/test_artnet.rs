@@ -0,0 +1,52 @@
+use laserport::dmx::{DmxController, DmxState};
+use laserport::transport::artnet::{self, ArtNetAddress};
+use laserport::transport::ArtNetTransport;
+use std::net::UdpSocket;
+use std::time::Duration;
+
+#[test]
+fn test_port_address() {
+    let address = ArtNetAddress::new(1, 2, 3).unwrap();
+    assert_eq!(address.port_address(), 0x0123);
+    assert_eq!(ArtNetAddress::from_port_address(0x0123), address);
+    assert!(ArtNetAddress::new(128, 0, 0).is_err());
+    assert!(ArtNetAddress::new(0, 16, 0).is_err());
+}
+
+#[test]
+fn test_encode_pads_odd_length() {
+    let packet = artnet::encode_art_dmx(7, ArtNetAddress::new(0, 0, 1).unwrap(), &[255, 70, 3]);
+    assert_eq!(&packet[16..18], &[0, 4]);
+    assert_eq!(&packet[18..], &[255, 70, 3, 0]);
+}
+
+#[test]
+fn test_art_dmx_over_udp() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+
+    let address = ArtNetAddress::new(2, 3, 4).unwrap();
+    let transport = ArtNetTransport::unicast(receiver.local_addr().unwrap(), address);
+    let mut controller = DmxController::with_transport(Box::new(transport), 1).unwrap();
+
+    let mut state = DmxState::new(16);
+    state.set_channel(1, 255);
+    state.set_channel(2, 70);
+    controller.send(&state).unwrap();
+    controller.send(&state).unwrap();
+
+    let mut buf = [0u8; 1024];
+    for expected_sequence in 1..=2u8 {
+        let len = receiver.recv(&mut buf).unwrap();
+        let packet = &buf[..len];
+        assert_eq!(len, 18 + 512);
+        assert_eq!(&packet[..8], b"Art-Net\0");
+        assert_eq!(&packet[8..10], &[0x00, 0x50]); // OpCode 0x5000, little-endian
+        assert_eq!(&packet[10..12], &[0, 14]);
+        assert_eq!(packet[12], expected_sequence);
+        assert_eq!(packet[14], 0x34); // Sub-net 3, universe 4
+        assert_eq!(packet[15], 2);
+        assert_eq!(&packet[16..18], &[0x02, 0x00]);
+        assert_eq!(&packet[18..20], &[255, 70]);
+    }
+}
@@ -0,0 +1,144 @@
+use laserport::dmx::{DmxState, FrameSource, DMX_FRAME_SIZE};
+use laserport::dmxcharts::ZQ03268::{GraphicsGroup, LaserState, MainSwitch};
+use laserport::playback::{Chase, ChaseTiming, Direction, FadeProfile, Step};
+use std::time::{Duration, Instant};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Steps that put their own index on channel 1.
+fn numbered(count: usize, hold: Duration) -> Vec<Step> {
+    (0..count).map(|index| Step::new(hold).with_channel(1, index as u8)).collect()
+}
+
+/// Channel 1 at each of `times` (in milliseconds after `start`).
+fn sample(chase: &mut Chase, start: Instant, times: impl IntoIterator<Item = u64>) -> Vec<u8> {
+    times
+        .into_iter()
+        .map(|at| {
+            let mut frame = DmxState::new(DMX_FRAME_SIZE);
+            chase.render(start + ms(at), &mut frame);
+            frame.channels[0]
+        })
+        .collect()
+}
+
+#[test]
+fn test_directions() {
+    let start = Instant::now();
+    let every_step = (0..10).map(|step| step * 100 + 50);
+    let run = |direction| {
+        let mut chase = Chase::new(numbered(4, ms(100))).unwrap().with_direction(direction);
+        chase.start();
+        sample(&mut chase, start, std::iter::once(0).chain(every_step.clone()))[1..].to_vec()
+    };
+    assert_eq!(run(Direction::Forward), [0, 1, 2, 3, 0, 1, 2, 3, 0, 1]);
+    assert_eq!(run(Direction::Reverse), [3, 2, 1, 0, 3, 2, 1, 0, 3, 2]);
+    assert_eq!(run(Direction::Bounce), [0, 1, 2, 3, 2, 1, 0, 1, 2, 3]);
+
+    let mut chase = Chase::new(numbered(4, ms(100))).unwrap().with_direction(Direction::Random).with_seed(7);
+    chase.start();
+    let steps = sample(&mut chase, start, std::iter::once(0).chain(every_step))[1..].to_vec();
+    assert!(steps.windows(2).all(|pair| pair[0] != pair[1]), "{:?}", steps);
+}
+
+#[test]
+fn test_loops_hold_the_last_step() {
+    let start = Instant::now();
+    let mut chase = Chase::new(numbered(3, ms(100))).unwrap().with_direction(Direction::Bounce).with_loops(1);
+    chase.start();
+    let steps = sample(&mut chase, start, [0, 150, 250, 350, 450, 550, 650]);
+    assert_eq!(steps, [0, 1, 2, 1, 0, 0, 0]);
+    assert!(!chase.is_running());
+    assert_eq!(chase.current_step(), Some(0));
+
+    chase.stop();
+    assert_eq!(sample(&mut chase, start, [700]), [0]);
+    assert_eq!(chase.current_step(), None);
+}
+
+#[test]
+fn test_step_fades_use_the_profile() {
+    let mut a = LaserState::new();
+    a.ch1 = MainSwitch::On;
+    a.ch4 = GraphicsGroup::Static1;
+    a.ch8 = 0;
+    let mut b = a;
+    b.ch4 = GraphicsGroup::Animation1;
+    b.ch8 = 200;
+    let steps = vec![
+        Step::new(ms(1000)).with_fixture(1, &a).unwrap(),
+        Step::new(ms(1000)).with_fixture(1, &b).unwrap().with_fade(ms(1000)),
+    ];
+    let mut chase = Chase::new(steps).unwrap().with_profile(FadeProfile::new().with_fixture(1, &a));
+    let start = Instant::now();
+    chase.start();
+    let mut frame = DmxState::new(DMX_FRAME_SIZE);
+    chase.render(start, &mut frame);
+    chase.render(start + ms(1500), &mut frame);
+    let state = LaserState::from_dmx_state(&frame, 1).unwrap();
+    assert_eq!((state.ch4, state.ch8), (GraphicsGroup::Animation1, 100));
+}
+
+#[test]
+fn test_bpm_and_tap_tempo() {
+    let start = Instant::now();
+    let mut chase = Chase::new(numbered(12, Duration::from_secs(10))).unwrap().with_bpm(120.0);
+    chase.start();
+    assert_eq!(sample(&mut chase, start, [0, 400, 600, 1100, 1600]), [0, 0, 1, 2, 3]);
+
+    // Tapping at 100 BPM: 600ms beats, timed from the current step
+    for beat in 0..4 {
+        chase.tap_at(start + ms(2000 + beat * 600));
+    }
+    assert!((chase.bpm().unwrap() - 100.0).abs() < 0.1);
+    // A tap long after the last one starts over and needs a second tap
+    chase.tap_at(start + ms(9000));
+    assert!((chase.bpm().unwrap() - 100.0).abs() < 0.1);
+    chase.tap_at(start + ms(9500));
+    assert!((chase.bpm().unwrap() - 120.0).abs() < 0.1);
+
+    // Step times are used until tapping switches to a tempo
+    let mut chase = Chase::new(numbered(2, ms(100))).unwrap();
+    assert_eq!(chase.bpm(), None);
+    chase.tap_at(start);
+    chase.tap_at(start + ms(250));
+    assert_eq!(chase.bpm(), Some(240.0));
+    chase.start();
+    assert_eq!(sample(&mut chase, start, [0, 200, 300]), [0, 0, 1]);
+}
+
+#[test]
+fn test_tempo_crossfade_and_empty_chase() {
+    let steps = vec![Step::new(ms(0)).with_channel(1, 0), Step::new(ms(0)).with_channel(1, 200)];
+    let timing = ChaseTiming::Tempo { bpm: 60.0, beats: 2.0, fade: 0.5 };
+    let mut chase = Chase::new(steps).unwrap().with_timing(timing);
+    let start = Instant::now();
+    chase.start();
+    assert_eq!(sample(&mut chase, start, [0, 2000, 2500, 3000, 3500]), [0, 0, 100, 200, 200]);
+    assert!(Chase::new(Vec::new()).is_err());
+}
+
+#[test]
+fn test_out_of_range_timing_keeps_running() {
+    let start = Instant::now();
+    // Steps too long to represent hold the first look instead of panicking
+    for timing in [
+        ChaseTiming::Tempo { bpm: 120.0, beats: f32::INFINITY, fade: f32::NAN },
+        ChaseTiming::Tempo { bpm: 120.0, beats: 1e30, fade: 1.0 },
+    ] {
+        let mut chase = Chase::new(numbered(3, ms(100))).unwrap().with_timing(timing);
+        chase.start();
+        assert_eq!(sample(&mut chase, start, [0, 1000, 60_000]), [0, 0, 0], "{:?}", timing);
+    }
+    let mut chase = Chase::new(vec![Step::new(Duration::MAX).with_fade(Duration::MAX).with_channel(1, 200)]).unwrap();
+    chase.start();
+    assert_eq!(sample(&mut chase, start, [0, 60_000]), [0, 0]);
+
+    // NaN beats and bpm are the shortest step rather than a panic
+    let timing = ChaseTiming::Tempo { bpm: f32::NAN, beats: f32::NAN, fade: 0.0 };
+    let mut chase = Chase::new(numbered(3, ms(100))).unwrap().with_timing(timing);
+    chase.start();
+    assert_eq!(sample(&mut chase, start, [0, 1]), [0, 1]);
+}
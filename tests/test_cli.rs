@@ -0,0 +1,83 @@
+#![cfg(unix)]
+
+use laserport::dmxcharts::ZQ03268::{ColorMode, GraphicsGroup, LaserState, MainSwitch};
+use laserport::transport::loopback::PtyLoopback;
+use std::process::{Command, Output};
+use std::time::Duration;
+
+const WAIT: Duration = Duration::from_secs(2);
+
+fn laserport(loopback: &PtyLoopback, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_laserport"))
+        .args(["--port", loopback.port_name(), "--adapter", "open-dmx"])
+        .args(args)
+        .env("LASERPORT_ADAPTERS", "/nonexistent/adapters.json")
+        .output()
+        .unwrap()
+}
+
+fn frame(loopback: &PtyLoopback, args: &[&str]) -> Vec<u8> {
+    let output = laserport(loopback, args);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    loopback.recv_frame(WAIT).expect("no frame sent")
+}
+
+#[test]
+fn test_set_send_and_blackout() {
+    let loopback = PtyLoopback::open_dmx().unwrap();
+
+    let slots = frame(&loopback, &["set", "1=255", "5=12", "20-22=7"]);
+    assert_eq!((slots[1], slots[5], slots[19], slots[20], slots[22], slots[23]), (255, 12, 0, 7, 7, 0));
+
+    let slots = frame(&loopback, &["send", "--address", "17", "255", "70"]);
+    assert_eq!(&slots[16..19], &[0, 255, 70]);
+
+    let slots = frame(&loopback, &["blackout"]);
+    assert!(slots.iter().all(|&value| value == 0));
+}
+
+#[test]
+fn test_laser_zq03268() {
+    let loopback = PtyLoopback::open_dmx().unwrap();
+    let args = ["laser", "zq03268", "--address", "17", "--color", "rainbow", "--group", "anim2", "--pattern", "12"];
+    let slots = frame(&loopback, &args);
+
+    let state = LaserState::from_channels(&slots[17..33].try_into().unwrap());
+    assert_eq!(state.ch1, MainSwitch::On);
+    assert_eq!(state.ch2, ColorMode::Rainbow);
+    assert_eq!(state.ch4, GraphicsGroup::Animation2);
+    assert_eq!(state.ch5, 12);
+
+    let slots = frame(&loopback, &["laser", "zq03268", "--color", "#ee1010"]);
+    assert_eq!(ColorMode::from_u8(slots[2]), ColorMode::FixedRed);
+}
+
+#[test]
+fn test_watch_keeps_sending() {
+    let loopback = PtyLoopback::open_dmx().unwrap();
+    let output = laserport(&loopback, &["watch", "--rate", "40", "--seconds", "0.3", "set", "1=9"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("fps"));
+
+    let frames = loopback.drain_frames();
+    assert!(frames.len() >= 5, "only {} frames", frames.len());
+    assert!(frames.iter().all(|frame| frame[1] == 9));
+}
+
+#[test]
+fn test_rejects_bad_arguments() {
+    let loopback = PtyLoopback::open_dmx().unwrap();
+    for args in [&["set", "0=1"][..], &["set", "1=256"], &["set", "5-2=1"], &["laser", "zq03268", "--color", "mauve"]] {
+        let output = laserport(&loopback, args);
+        assert!(!output.status.success(), "{:?} succeeded", args);
+    }
+    for seconds in ["inf", "NaN", "-1", "1e30", "soon"] {
+        let output = laserport(&loopback, &["watch", &format!("--seconds={}", seconds), "blackout"]);
+        assert!(!output.status.success(), "--seconds {} succeeded", seconds);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--seconds"), "--seconds {}", seconds);
+    }
+    assert!(loopback.drain_frames().is_empty());
+
+    let output = laserport(&loopback, &["send", "--address", "512", "1", "2"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("don't fit"));
+}
@@ -0,0 +1,96 @@
+#![cfg(unix)]
+
+use laserport::dmxcharts::ZQ03268::{ColorMode, LaserState, MainSwitch};
+use laserport::transport::loopback::PtyLoopback;
+use serialport::{SerialPort, TTYPort};
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+const DOWN: &str = "\x1b[B";
+const SETTLE: Duration = Duration::from_millis(300);
+
+/// `laserport console` running on a terminal of its own, driven by keystrokes.
+struct Console {
+    child: Child,
+    terminal: TTYPort,
+    _screen: thread::JoinHandle<()>,
+}
+
+impl Console {
+    fn start(loopback: &PtyLoopback) -> Self {
+        let (terminal, slave) = TTYPort::pair().unwrap();
+        let tty = || OpenOptions::new().read(true).write(true).open(slave.name().unwrap()).unwrap();
+        let status = Command::new("stty").args(["cols", "120", "rows", "40"]).stdin(tty()).status().unwrap();
+        assert!(status.success());
+
+        let child = Command::new(env!("CARGO_BIN_EXE_laserport"))
+            .args(["--port", loopback.port_name(), "--adapter", "open-dmx", "console"])
+            .env("LASERPORT_ADAPTERS", "/nonexistent/adapters.json")
+            .stdin(tty())
+            .stdout(tty())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        // Keep reading the screen so the console never blocks on a full PTY buffer
+        let mut screen = terminal.try_clone_native().unwrap();
+        screen.set_timeout(Duration::from_secs(60)).unwrap();
+        let _screen = thread::spawn(move || {
+            let mut buffer = [0u8; 4096];
+            while screen.read(&mut buffer).is_ok_and(|n| n > 0) {}
+        });
+        drop(slave);
+        thread::sleep(SETTLE);
+        Console { child, terminal, _screen }
+    }
+
+    fn press(&mut self, keys: &str) {
+        self.terminal.write_all(keys.as_bytes()).unwrap();
+        thread::sleep(SETTLE);
+    }
+
+    fn quit(mut self) {
+        self.press("q");
+        let output = self.child.wait_with_output().unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+}
+
+fn last_state(loopback: &PtyLoopback) -> (Vec<u8>, LaserState) {
+    let frames = loopback.drain_frames();
+    let last = frames.last().expect("no frames sent").clone();
+    let state = LaserState::from_channels(&last[1..17].try_into().unwrap());
+    (last, state)
+}
+
+#[test]
+fn test_faders_dropdown_and_blackout() {
+    let loopback = PtyLoopback::open_dmx().unwrap();
+    let mut console = Console::start(&loopback);
+
+    // CH1 to full, then CH2's dropdown down to the 8-segment colour
+    console.press("\x1b[H");
+    console.press("l\r");
+    console.press(&DOWN.repeat(13));
+    console.press("\r");
+    // CH8 size from the top: three steps down
+    console.press("llllll\x1b[H");
+    console.press("jjj");
+    let (_, state) = last_state(&loopback);
+    assert_eq!(state.ch1, MainSwitch::On);
+    assert_eq!(state.ch2, ColorMode::Seg8);
+    assert_eq!(state.ch8, 252);
+
+    console.press(" ");
+    let (frame, _) = last_state(&loopback);
+    assert!(frame.iter().all(|&value| value == 0), "{:?}", frame);
+
+    console.press(" ");
+    let (_, state) = last_state(&loopback);
+    assert_eq!((state.ch1, state.ch2, state.ch8), (MainSwitch::On, ColorMode::Seg8, 252));
+
+    console.quit();
+}
@@ -0,0 +1,96 @@
+use laserport::dmx::{DmxState, FrameSource, DMX_FRAME_SIZE};
+use laserport::dmxcharts::ZQ03268::{
+    self, ColorMode, GraphicsGroup, LaserState, MainSwitch, MovementHorizontal, CHANNEL_COUNT,
+};
+use laserport::fixture::{DefinedFixture, FadeBehaviour, Fixture, RawFixture};
+use laserport::patch::Patch;
+use laserport::playback::{Crossfade, Cue, CueList, FadeProfile, Playback};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn laser(group: GraphicsGroup, size: u8, x: MovementHorizontal) -> LaserState {
+    let mut state = LaserState::new();
+    state.ch1 = MainSwitch::On;
+    state.ch2 = ColorMode::FixedRed;
+    state.ch4 = group;
+    state.ch8 = size;
+    state.ch13 = x;
+    state
+}
+
+#[test]
+fn test_zq03268_declares_behaviours() {
+    let state = LaserState::new();
+    let behaviours = state.fade_behaviours();
+    assert_eq!(behaviours.len(), CHANNEL_COUNT);
+    assert_eq!(behaviours[7], FadeBehaviour::Fade);
+    assert_eq!(behaviours[1], FadeBehaviour::Snap);
+    assert!(behaviours[12].fades(10, 100));
+    assert!(!behaviours[12].fades(100, 200));
+
+    // The bundled OFL definition gives the same answer through its capability types
+    let defined = DefinedFixture::new(Arc::new(ZQ03268::definition()), "16-channel").unwrap();
+    assert_eq!(defined.fade_behaviours(), behaviours);
+
+    // Fixtures without a model fade everything
+    assert!(RawFixture::new(3).fade_behaviours().iter().all(|behaviour| *behaviour == FadeBehaviour::Fade));
+}
+
+#[test]
+fn test_crossfade_snaps_selectors_and_fades_sizes() {
+    let from = laser(GraphicsGroup::Static1, 0, MovementHorizontal::Position(0));
+    let to = laser(GraphicsGroup::Animation5, 200, MovementHorizontal::Position(100));
+    let mut patch = Patch::new();
+    patch.add("laser", 1, Box::new(from)).unwrap();
+    patch.add("dimmer", 20, Box::new(RawFixture::new(1))).unwrap();
+    let from_universe = patch.render();
+    let mut to_universe = DmxState::new(DMX_FRAME_SIZE);
+    to_universe.channels[..CHANNEL_COUNT].copy_from_slice(&to.to_channels());
+    to_universe.set_channel(20, 100);
+
+    let crossfade = Crossfade::new(from_universe, to_universe, FadeProfile::from_patch(&patch)).with_snap_at(0.5);
+    let quarter = crossfade.at(0.25);
+    assert_eq!(quarter.get_channel(4), Some(GraphicsGroup::Static1.to_u8()));
+    assert_eq!(quarter.get_channel(8), Some(50));
+    assert_eq!(quarter.get_channel(13), Some(25));
+    assert_eq!(quarter.get_channel(20), Some(25));
+
+    let half = crossfade.at(0.5);
+    assert_eq!(half.get_channel(4), Some(GraphicsGroup::Animation5.to_u8()));
+    assert_eq!(half.get_channel(8), Some(100));
+    assert_eq!(crossfade.at(1.0), crossfade.to);
+}
+
+#[test]
+fn test_position_to_movement_snaps() {
+    let from = laser(GraphicsGroup::Static1, 0, MovementHorizontal::Position(20));
+    let to = laser(GraphicsGroup::Static1, 0, MovementHorizontal::CircularSpeed(100));
+    let profile = FadeProfile::new().with_fixture(1, &from);
+    let crossfade = Crossfade::new(from.to_dmx_state(), to.to_dmx_state(), profile);
+    assert_eq!(crossfade.at(0.1).get_channel(13), Some(MovementHorizontal::CircularSpeed(100).to_u8()));
+}
+
+#[test]
+fn test_playback_uses_profile() {
+    let from = laser(GraphicsGroup::Static2, 40, MovementHorizontal::Position(0));
+    let to = laser(GraphicsGroup::Animation1, 240, MovementHorizontal::Position(0));
+    let list = CueList::new("show")
+        .with_cue(Cue::new("a").with_fixture(1, &from).unwrap())
+        .with_cue(Cue::new("b").with_fixture(1, &to).unwrap().with_fade(Duration::from_secs(1)).with_snap_at(0.75));
+    let mut playback = Playback::new(list).with_profile(FadeProfile::new().with_fixture(1, &from));
+    let start = Instant::now();
+    let render = |playback: &mut Playback, ms: u64| {
+        let mut frame = DmxState::new(DMX_FRAME_SIZE);
+        playback.render(start + Duration::from_millis(ms), &mut frame);
+        LaserState::from_dmx_state(&frame, 1).unwrap()
+    };
+
+    playback.go();
+    render(&mut playback, 0);
+    playback.go();
+    render(&mut playback, 0);
+    let state = render(&mut playback, 500);
+    assert_eq!((state.ch4, state.ch8), (GraphicsGroup::Static2, 140));
+    let state = render(&mut playback, 800);
+    assert_eq!((state.ch4, state.ch8), (GraphicsGroup::Animation1, 200));
+}
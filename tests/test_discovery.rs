@@ -0,0 +1,95 @@
+use laserport::transport::discovery::{self, DiscoveredPort, ProbeResult};
+use laserport::transport::enttec::{encode_message, LABEL_GET_SERIAL_NUMBER, LABEL_GET_WIDGET_PARAMETERS};
+use laserport::transport::mock::PortEvent;
+use laserport::transport::{SerialAdapter, VirtualPort};
+use serialport::{SerialPortInfo, SerialPortType, UsbPortInfo};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_millis(200);
+
+fn usb_port(name: &str, vid: u16, pid: u16, manufacturer: &str, product: &str) -> SerialPortInfo {
+    SerialPortInfo {
+        port_name: name.to_string(),
+        port_type: SerialPortType::UsbPort(UsbPortInfo {
+            vid,
+            pid,
+            serial_number: Some("EN123456".to_string()),
+            manufacturer: Some(manufacturer.to_string()),
+            product: Some(product.to_string()),
+        }),
+    }
+}
+
+#[test]
+fn test_describe_ports_from_descriptors() {
+    let pro = DiscoveredPort::from_info(&usb_port("COM3", 0x0403, 0x6001, "ENTTEC", "DMX USB PRO"));
+    assert!(pro.is_ftdi && pro.enttec_like);
+    assert_eq!(pro.serial_number.as_deref(), Some("EN123456"));
+    assert_eq!(pro.likely_adapter(), Some(SerialAdapter::EnttecPro));
+
+    let open_dmx = DiscoveredPort::from_info(&usb_port("COM4", 0x0403, 0x6001, "ENTTEC", "OPEN DMX USB"));
+    assert!(!open_dmx.enttec_like);
+    assert_eq!(open_dmx.likely_adapter(), Some(SerialAdapter::OpenDmx));
+
+    let ultra = DiscoveredPort::from_info(&usb_port("/dev/ttyUSB1", 0x0403, 0x6001, "DMXking.com", "ultraDMX Micro"));
+    assert_eq!(ultra.likely_adapter(), Some(SerialAdapter::EnttecPro));
+
+    let ch340 = DiscoveredPort::from_info(&usb_port("/dev/ttyUSB0", 0x1a86, 0x7523, "QinHeng", "USB Serial"));
+    assert_eq!(ch340.likely_adapter(), None);
+
+    let builtin = DiscoveredPort::from_info(&SerialPortInfo {
+        port_name: "COM1".to_string(),
+        port_type: SerialPortType::PciPort,
+    });
+    assert_eq!((builtin.vid, builtin.likely_adapter()), (None, None));
+}
+
+#[test]
+fn test_probe_reply_overrides_descriptors() {
+    let mut port = DiscoveredPort::from_info(&usb_port("COM4", 0x0403, 0x6001, "FTDI", "FT232R USB UART"));
+    assert_eq!(port.likely_adapter(), Some(SerialAdapter::OpenDmx));
+    port.probe = Some(ProbeResult::EnttecPro { firmware_version: 0x144, serial_number: "12345678".to_string() });
+    assert_eq!(port.likely_adapter(), Some(SerialAdapter::EnttecPro));
+    port.probe = Some(ProbeResult::Unavailable("busy".to_string()));
+    assert_eq!(port.likely_adapter(), None);
+}
+
+#[test]
+fn test_probe_virtual_ports() {
+    let silent = VirtualPort::new("virtual0");
+    let recording = silent.recording();
+    assert_eq!(discovery::probe(Box::new(silent), TIMEOUT), ProbeResult::NoReply);
+    // Only the parameters request went out: no break and no DMX frame.
+    let events = recording.events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].1, PortEvent::Write(encode_message(LABEL_GET_WIDGET_PARAMETERS, &[0, 0])));
+
+    let widget = VirtualPort::new("virtual1");
+    widget.push_input(&encode_message(LABEL_GET_WIDGET_PARAMETERS, &[0x44, 0x01, 9, 1, 40]));
+    widget.push_input(&encode_message(LABEL_GET_SERIAL_NUMBER, &[0x21, 0x43, 0x65, 0x87]));
+    assert_eq!(
+        discovery::probe(Box::new(widget), TIMEOUT),
+        ProbeResult::EnttecPro { firmware_version: 0x144, serial_number: "87654321".to_string() }
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_probe_pty_adapters() {
+    use laserport::transport::loopback::{LoopbackAdapter, PtyLoopback};
+
+    let widget = PtyLoopback::open(LoopbackAdapter::EnttecPro { serial_number: 12345678 }).unwrap();
+    match discovery::probe_port(widget.port_name(), TIMEOUT) {
+        ProbeResult::EnttecPro { serial_number, .. } => assert_eq!(serial_number, "12345678"),
+        other => panic!("unexpected probe result {:?}", other),
+    }
+
+    let cable = PtyLoopback::open_dmx().unwrap();
+    assert_eq!(discovery::probe_port(cable.port_name(), TIMEOUT), ProbeResult::NoReply);
+    assert!(cable.drain_frames().is_empty());
+
+    assert!(matches!(
+        discovery::probe_port("/dev/laserport-missing", TIMEOUT),
+        ProbeResult::Unavailable(_)
+    ));
+}
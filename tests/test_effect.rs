@@ -0,0 +1,113 @@
+use laserport::dmx::{DmxController, DmxState, FrameSource, DMX_FRAME_SIZE};
+use laserport::dmxcharts::ZQ03268::{LaserState, MovementHorizontal};
+use laserport::patch::Patch;
+use laserport::playback::{Cue, CueList, Effects, Lfo, Playback, Waveform};
+use laserport::transport::VirtualPort;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Channel `channel` after rendering `effects` over `underneath` at `start + ms`.
+fn sample(effects: &mut Effects, start: Instant, ms: u64, channel: usize, underneath: u8) -> u8 {
+    let mut frame = DmxState::new(DMX_FRAME_SIZE);
+    frame.set_channel(channel, underneath);
+    effects.render(start + Duration::from_millis(ms), &mut frame);
+    frame.get_channel(channel).unwrap()
+}
+
+#[test]
+fn test_waveforms() {
+    let start = Instant::now();
+    let shape = |waveform| {
+        let mut effects = Effects::new();
+        effects.add(8, Lfo::new(waveform, 1.0, 100.0));
+        [0, 125, 250, 500, 750, 875].map(|ms| sample(&mut effects, start, ms, 8, 120))
+    };
+    assert_eq!(shape(Waveform::Sine), [120, 191, 220, 120, 20, 49]);
+    assert_eq!(shape(Waveform::Triangle), [120, 170, 220, 120, 20, 70]);
+    assert_eq!(shape(Waveform::Saw), [20, 45, 70, 120, 170, 195]);
+    assert_eq!(shape(Waveform::Square), [220, 220, 220, 20, 20, 20]);
+
+    // Phase shifts the cycle; the clock starts at the first frame rendered after adding
+    let mut effects = Effects::new();
+    effects.add(1, Lfo::new(Waveform::Square, 1.0, 100.0).with_phase(0.5));
+    assert_eq!(sample(&mut effects, start, 300, 1, 120), 20);
+    assert_eq!(sample(&mut effects, start, 850, 1, 120), 220);
+}
+
+#[test]
+fn test_sweep_position_and_clamp() {
+    let start = Instant::now();
+    let mut effects = Effects::new();
+    effects.add(13, Lfo::between(Waveform::Triangle, 0.5, 0, 127));
+    let sweep: Vec<u8> = (0..=8).map(|step| sample(&mut effects, start, step * 250, 13, 200)).collect();
+    assert_eq!(sweep, [64, 95, 127, 95, 64, 32, 0, 32, 64]);
+    assert!(sweep.iter().all(|&value| MovementHorizontal::from_u8(value) == MovementHorizontal::Position(value)));
+
+    // Relative effects stack on the value underneath and stay inside the range
+    let mut effects = Effects::new();
+    effects.add(8, Lfo::new(Waveform::Square, 1.0, 50.0));
+    effects.add(8, Lfo::new(Waveform::Square, 1.0, 50.0).with_offset(10.0).with_range(0..=200));
+    assert_eq!(sample(&mut effects, start, 0, 8, 90), 200);
+    assert_eq!(sample(&mut effects, start, 600, 8, 90), 0);
+
+    // A reversed range is put the right way round rather than panicking in the clamp
+    let (high, low) = (150, 60);
+    let lfo = Lfo::new(Waveform::Square, 1.0, 50.0).with_range(high..=low);
+    assert_eq!(lfo.range(), 60..=150);
+    let mut effects = Effects::new();
+    effects.add(8, lfo);
+    assert_eq!(sample(&mut effects, start, 0, 8, 90), 140);
+    assert_eq!(sample(&mut effects, start, 600, 8, 90), 60);
+}
+
+#[test]
+fn test_random_walk_stays_in_range() {
+    let start = Instant::now();
+    let walk = |seed| {
+        let mut effects = Effects::new().with_seed(seed);
+        effects.add(8, Lfo::new(Waveform::RandomWalk, 2.0, 100.0).with_range(50..=150));
+        (0..200).map(|frame| sample(&mut effects, start, frame * 25, 8, 100)).collect::<Vec<u8>>()
+    };
+    let values = walk(3);
+    assert_eq!(values, walk(3));
+    assert!(values.iter().all(|value| (50..=150).contains(value)));
+    // At 2 Hz a full swing takes a quarter second, so one 25ms frame moves at most 20
+    assert!(values.windows(2).all(|pair| pair[0].abs_diff(pair[1]) <= 20), "{:?}", values);
+    assert!(values.iter().min() < Some(&80) && values.iter().max() > Some(&120), "{:?}", values);
+}
+
+#[test]
+fn test_effects_over_a_cue_in_refresh_loop() {
+    let mut laser = LaserState::new();
+    laser.ch8 = 100;
+    let mut patch = Patch::new();
+    patch.add("laser1", 17, Box::new(laser)).unwrap();
+    let size = patch.channel("laser1", "Pattern size").unwrap();
+    assert_eq!(size, 24);
+    assert_eq!(patch.channel("laser1", "Colour"), None);
+
+    let playback = Playback::new(CueList::new("show").with_cue(Cue::new("look").with_patch(&patch)));
+    let effects = Effects::new();
+    let pulse = effects.add(size, Lfo::new(Waveform::Square, 4.0, 50.0));
+
+    let port = VirtualPort::new("virtual0");
+    let recording = port.recording();
+    let controller = DmxController::with_transport(Box::new(port.open_dmx()), 1).unwrap();
+    let refresh = controller.start_refresh(DmxState::new(DMX_FRAME_SIZE), 40.0).unwrap();
+    // Queued before attaching, so the first frame the playback renders already has the cue
+    playback.go();
+    refresh.handle().add_source(Box::new(playback.clone()));
+    refresh.handle().add_source(Box::new(effects.clone()));
+    thread::sleep(Duration::from_millis(400));
+    assert!(effects.remove(pulse));
+    assert!(!effects.remove(pulse));
+    thread::sleep(Duration::from_millis(100));
+    refresh.stop().unwrap();
+
+    // Frames sent before the sources were attached carry the empty base state
+    let sizes: Vec<u8> =
+        recording.frames().iter().map(|frame| frame.channel(size).unwrap()).skip_while(|&size| size == 0).collect();
+    assert!(sizes.contains(&150) && sizes.contains(&50), "{:?}", sizes);
+    assert!(sizes.iter().all(|size| [50, 100, 150].contains(size)), "{:?}", sizes);
+    assert_eq!(sizes.last(), Some(&100));
+}
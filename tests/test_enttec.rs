@@ -0,0 +1,56 @@
+use laserport::transport::enttec::{self, WidgetParameters};
+use laserport::dmx::DmxTiming;
+use std::io::Cursor;
+use std::time::Duration;
+
+#[test]
+fn test_encode_send_dmx_packet() {
+    let message = enttec::encode_message(enttec::LABEL_SEND_DMX_PACKET, &[0x00, 255, 70]);
+    assert_eq!(message, vec![0x7E, 6, 3, 0, 0x00, 255, 70, 0xE7]);
+}
+
+#[test]
+fn test_encode_length_is_lsb_first() {
+    let payload = vec![0u8; 513];
+    let message = enttec::encode_message(enttec::LABEL_SEND_DMX_PACKET, &payload);
+    assert_eq!(&message[..4], &[0x7E, 6, 0x01, 0x02]);
+    assert_eq!(message.len(), 513 + 5);
+    assert_eq!(*message.last().unwrap(), 0xE7);
+}
+
+#[test]
+fn test_read_message_skips_noise() {
+    let mut bytes = vec![0x00, 0x13];
+    bytes.extend(enttec::encode_message(enttec::LABEL_GET_SERIAL_NUMBER, &[0x78, 0x56, 0x34, 0x12]));
+    let (label, payload) = enttec::read_message(&mut Cursor::new(bytes)).unwrap();
+    assert_eq!(label, enttec::LABEL_GET_SERIAL_NUMBER);
+    assert_eq!(enttec::decode_serial_number(&payload).unwrap(), "12345678");
+}
+
+#[test]
+fn test_read_message_requires_end_delimiter() {
+    let bytes = vec![0x7E, 3, 1, 0, 0xAA, 0x00];
+    assert!(enttec::read_message(&mut Cursor::new(bytes)).is_err());
+}
+
+#[test]
+fn test_widget_parameters() {
+    let params = WidgetParameters::from_payload(&[0x44, 0x01, 9, 1, 40]).unwrap();
+    assert_eq!(params.firmware_version, 0x0144);
+    assert!((params.break_time_us - 96.03).abs() < 0.01);
+    assert!((params.mab_time_us - 10.67).abs() < 0.01);
+    assert_eq!(params.refresh_rate, 40);
+}
+
+#[test]
+fn test_timing_payload() {
+    let timing = DmxTiming {
+        break_time: Duration::from_micros(176),
+        mark_after_break: Duration::from_micros(12),
+        ..DmxTiming::default()
+    };
+    assert_eq!(enttec::timing_payload(&timing, 0), vec![0, 0, 17, 2, 0]);
+
+    let timing = DmxTiming { break_time: Duration::from_micros(92), ..DmxTiming::default() };
+    assert_eq!(enttec::timing_payload(&timing, 50)[2..], [9, 2, 40]);
+}
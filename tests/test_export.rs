@@ -0,0 +1,153 @@
+use laserport::dmxcharts::ZQ03268;
+use laserport::fixture::{ofl, qxf, Capability, ChannelDefinition, FixtureDefinition, Meta, ModeDefinition, Physical};
+use serde_json::Value;
+
+#[test]
+fn test_ofl_export_round_trip() {
+    let definition = ZQ03268::definition();
+    let exported = ofl::export(&definition, "2000-01-01");
+    assert_eq!(ofl::parse(&exported, &definition.manufacturer).unwrap(), definition);
+
+    // Meta block included, the export is the bundled file; its own dates win over the one passed in.
+    let exported: Value = serde_json::from_str(&exported).unwrap();
+    let bundled: Value = serde_json::from_str(ZQ03268::OFL_DEFINITION).unwrap();
+    assert_eq!(exported, bundled);
+}
+
+#[test]
+fn test_qxf_export_round_trip() {
+    let json = r##"{
+        "name": "Party Laser",
+        "categories": ["Laser", "Effect"],
+        "meta": { "authors": ["someone"], "createDate": "2024-01-31", "lastModifyDate": "2024-01-31" },
+        "physical": {
+            "dimensions": [200, 90, 170],
+            "weight": 1.5,
+            "power": 20,
+            "DMXconnector": "3-pin",
+            "bulb": { "type": "Laser" }
+        },
+        "availableChannels": {
+            "Dimmer": { "defaultValue": 255, "capability": { "type": "Intensity" } },
+            "Strobe": {
+                "capabilities": [
+                    { "dmxRange": [0, 9], "type": "NoFunction", "comment": "Open" },
+                    {
+                        "dmxRange": [10, 255],
+                        "type": "ShutterStrobe",
+                        "shutterEffect": "Strobe",
+                        "comment": "Strobe slow to fast"
+                    }
+                ]
+            },
+            "Color": {
+                "capabilities": [
+                    { "dmxRange": [0, 127], "type": "ColorPreset", "colors": ["#ff0000"], "comment": "Red" },
+                    { "dmxRange": [128, 255], "type": "Effect", "effectName": "Rainbow" }
+                ]
+            },
+            "Spare": { "capability": { "type": "NoFunction" } }
+        },
+        "modes": [{ "name": "4-channel", "shortName": "4ch", "channels": ["Dimmer", "Strobe", "Color", "Spare"] }]
+    }"##;
+    let definition = ofl::parse(json, "Generic").unwrap();
+
+    // QLC+ types channels, not capabilities, and has no short names, second category, meta or OFL properties.
+    let channel = |name: &str, default_value: u8, capabilities: Vec<Capability>| ChannelDefinition {
+        name: name.to_string(),
+        default_value,
+        capabilities,
+        fine_channel_aliases: Vec::new(),
+    };
+    let expected = FixtureDefinition {
+        manufacturer: "Generic".to_string(),
+        name: "Party Laser".to_string(),
+        categories: vec!["Laser".to_string()],
+        physical: Physical {
+            dimensions_mm: Some([200.0, 90.0, 170.0]),
+            weight_kg: Some(1.5),
+            power_w: Some(20.0),
+            dmx_connector: Some("3-pin".to_string()),
+            bulb: Some("Laser".to_string()),
+        },
+        meta: Meta { authors: vec!["someone".to_string()], ..Meta::default() },
+        channels: vec![
+            channel("Dimmer", 255, vec![Capability::new(0, 255, "Intensity", "Intensity")]),
+            channel(
+                "Strobe",
+                0,
+                vec![
+                    Capability::new(0, 9, "ShutterStrobe", "Open"),
+                    Capability::new(10, 255, "ShutterStrobe", "Strobe slow to fast"),
+                ],
+            ),
+            channel(
+                "Color",
+                0,
+                vec![Capability::new(0, 127, "ColorPreset", "Red"), Capability::new(128, 255, "ColorPreset", "Rainbow")],
+            ),
+            channel("Spare", 0, vec![Capability::new(0, 255, "NoFunction", "NoFunction")]),
+        ],
+        modes: vec![ModeDefinition {
+            name: "4-channel".to_string(),
+            short_name: None,
+            channels: vec!["Dimmer".into(), "Strobe".into(), "Color".into(), "Spare".into()],
+        }],
+    };
+    let exported = qxf::export(&definition);
+    let imported = qxf::parse(&exported).unwrap();
+    assert_eq!(imported, expected);
+    assert_eq!(qxf::export(&imported), exported);
+
+    let profile = qxf::load("tests/data/UKing-ZQ03268.qxf").unwrap();
+    assert_eq!(qxf::parse(&qxf::export(&profile)).unwrap(), profile);
+    // QLC+ files have an author but no dates; the OFL export is dated with the date passed in.
+    assert_eq!(profile.meta, Meta { authors: vec!["laserport".to_string()], ..Meta::default() });
+    let mut from_ofl = ofl::parse(&ofl::export(&profile, "2024-05-01"), &profile.manufacturer).unwrap();
+    assert_eq!(from_ofl.meta.authors, ["laserport"]);
+    assert_eq!(from_ofl.meta.create_date.as_deref(), Some("2024-05-01"));
+    assert_eq!(from_ofl.meta.last_modify_date.as_deref(), Some("2024-05-01"));
+    from_ofl.meta = profile.meta.clone();
+    assert_eq!(from_ofl, profile);
+}
+
+#[test]
+fn test_export_escapes_names_and_keeps_physical() {
+    let mut definition = ZQ03268::definition();
+    definition.name = r#"Laser <"Mini"> & Co"#.to_string();
+    definition.physical.dimensions_mm = Some([200.0, 90.0, 170.0]);
+    definition.physical.weight_kg = Some(1.5);
+    definition.physical.power_w = Some(20.0);
+    definition.channels[0].capabilities[1] = Capability::new(10, 255, "ShutterStrobe", "On & 'lit'");
+
+    let from_qxf = qxf::parse(&qxf::export(&definition)).unwrap();
+    assert_eq!(from_qxf.name, definition.name);
+    assert_eq!(from_qxf.physical, definition.physical);
+    assert_eq!(from_qxf.channels[0].capabilities[1].name, "On & 'lit'");
+
+    let from_ofl = ofl::parse(&ofl::export(&definition, "2000-01-01"), &definition.manufacturer).unwrap();
+    assert_eq!(from_ofl, definition);
+}
+
+#[test]
+fn test_ofl_export_keeps_fine_channel_aliases() {
+    let json = r#"{
+        "name": "Dimmer 16-bit",
+        "categories": ["Dimmer"],
+        "meta": { "authors": ["someone"], "createDate": "2024-01-31", "lastModifyDate": "2025-06-01" },
+        "availableChannels": {
+            "Dimmer": { "fineChannelAliases": ["Dimmer fine"], "capability": { "type": "Intensity" } }
+        },
+        "modes": [{ "name": "16-bit", "channels": ["Dimmer", "Dimmer fine"] }]
+    }"#;
+    let definition = ofl::parse(json, "generic").unwrap();
+    assert_eq!(definition.channels.len(), 2);
+
+    let exported = ofl::export(&definition, "2000-01-01");
+    let file: Value = serde_json::from_str(&exported).unwrap();
+    let channels = file["availableChannels"].as_object().unwrap();
+    assert_eq!(channels.keys().collect::<Vec<_>>(), ["Dimmer"]);
+    assert_eq!(channels["Dimmer"]["fineChannelAliases"], serde_json::json!(["Dimmer fine"]));
+    assert_eq!(file["meta"]["lastModifyDate"], "2025-06-01");
+    assert_eq!(ofl::parse(&exported, "generic").unwrap(), definition);
+}
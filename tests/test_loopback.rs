@@ -0,0 +1,80 @@
+#![cfg(unix)]
+
+use laserport::dmx::{DmxController, DmxState, DmxTiming};
+use laserport::transport::loopback::{FrameDecoder, LoopbackAdapter, PtyLoopback};
+use laserport::transport::{EnttecProTransport, SerialAdapter};
+use std::process::Command;
+use std::time::Duration;
+
+const WAIT: Duration = Duration::from_secs(2);
+
+#[test]
+fn test_frame_decoder_splits_stream() {
+    let mut decoder = FrameDecoder::new(3);
+    assert!(decoder.push(&[0, 1, 2]).is_empty());
+    assert_eq!(decoder.push(&[3, 0, 4, 5, 6, 0]), vec![vec![0, 1, 2, 3], vec![0, 4, 5, 6]]);
+    assert_eq!(decoder.push(&[7, 8, 9]), vec![vec![0, 7, 8, 9]]);
+}
+
+#[test]
+fn test_controller_new_over_pty() {
+    let loopback = PtyLoopback::open_dmx().unwrap();
+    let mut controller = DmxController::new(loopback.port_name(), 3).unwrap();
+
+    let mut state = DmxState::new(2);
+    state.set_channel(1, 255);
+    state.set_channel(2, 7);
+    controller.send(&state).unwrap();
+
+    let frame = loopback.recv_frame(WAIT).expect("no frame on the master side");
+    assert_eq!(frame.len(), 513);
+    assert_eq!(frame[0], 0);
+    assert_eq!(&frame[3..5], &[255, 7]);
+}
+
+#[test]
+fn test_short_frames_over_pty() {
+    let loopback = PtyLoopback::open(LoopbackAdapter::OpenDmx { slots: 24 }).unwrap();
+    let mut controller = DmxController::new(loopback.port_name(), 1).unwrap();
+    controller.set_timing(DmxTiming { slots: 24, ..DmxTiming::default() }).unwrap();
+
+    for value in 1..=3 {
+        let mut state = DmxState::new(1);
+        state.set_channel(1, value);
+        controller.send(&state).unwrap();
+    }
+    let frames: Vec<u8> = (0..3).map(|_| loopback.recv_frame(WAIT).unwrap()[1]).collect();
+    assert_eq!(frames, [1, 2, 3]);
+}
+
+#[test]
+fn test_enttec_pro_emulation() {
+    let loopback = PtyLoopback::open(LoopbackAdapter::EnttecPro { serial_number: 12345678 }).unwrap();
+
+    let mut widget = EnttecProTransport::new(loopback.port_name());
+    laserport::transport::DmxTransport::open(&mut widget).unwrap();
+    assert_eq!(widget.serial_number().unwrap(), "12345678");
+    assert_eq!(widget.widget_parameters().unwrap().refresh_rate, 40);
+    laserport::transport::DmxTransport::close(&mut widget).unwrap();
+
+    let mut controller = DmxController::with_adapter(loopback.port_name(), SerialAdapter::EnttecPro, 1).unwrap();
+    controller.set_timing(DmxTiming::default()).unwrap();
+    let mut state = DmxState::new(1);
+    state.set_channel(1, 42);
+    controller.send(&state).unwrap();
+
+    let frame = loopback.recv_frame(WAIT).unwrap();
+    assert_eq!(&frame[..2], &[0, 42]);
+    assert_eq!(loopback.widget_parameters()[2], 10); // 100µs break in 10.67µs units
+    assert_eq!(loopback.last_error(), None);
+}
+
+#[test]
+fn test_binary_drives_pty() {
+    let loopback = PtyLoopback::open_dmx().unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_test2")).arg(loopback.port_name()).status().unwrap();
+    assert!(status.success());
+
+    let frame = loopback.recv_frame(WAIT).unwrap();
+    assert_eq!(&frame[1..3], &[255, 70]);
+}
@@ -0,0 +1,123 @@
+use laserport::dmxcharts::ZQ03268::{self, ColorMode, LaserState, MainSwitch, CHANNEL_NAMES};
+use laserport::fixture::{ofl, Capability, DefinedFixture};
+use std::mem::discriminant;
+use std::sync::Arc;
+
+// Variant of the typed model a value decodes to on one channel, ignoring parameters.
+// Raw channels (CH5, CH7, CH8) have no variants.
+fn typed_variant(channel: usize, value: u8) -> Option<String> {
+    let mut channels = [0u8; 16];
+    channels[channel] = value;
+    let state = LaserState::from_channels(&channels);
+    let debug = match channel {
+        0 => format!("{:?}", discriminant(&state.ch1)),
+        1 => format!("{:?}", discriminant(&state.ch2)),
+        2 => format!("{:?}", discriminant(&state.ch3)),
+        3 => format!("{:?}", discriminant(&state.ch4)),
+        5 => format!("{:?}", discriminant(&state.ch6)),
+        8 => format!("{:?}", discriminant(&state.ch9)),
+        9 => format!("{:?}", discriminant(&state.ch10)),
+        10 => format!("{:?}", discriminant(&state.ch11)),
+        11 => format!("{:?}", discriminant(&state.ch12)),
+        12 => format!("{:?}", discriminant(&state.ch13)),
+        13 => format!("{:?}", discriminant(&state.ch14)),
+        14 => format!("{:?}", discriminant(&state.ch15)),
+        15 => format!("{:?}", discriminant(&state.ch16)),
+        _ => return None,
+    };
+    Some(debug)
+}
+
+#[test]
+fn test_bundled_definition_matches_typed_model() {
+    let definition = ZQ03268::definition();
+    assert_eq!(definition.manufacturer, "U'King");
+    let mode = definition.mode("16ch").unwrap();
+    assert_eq!(mode.channels, CHANNEL_NAMES);
+
+    let defaults = LaserState::new().to_channels();
+    for (index, name) in CHANNEL_NAMES.iter().enumerate() {
+        let channel = definition.channel(name).unwrap();
+        assert_eq!(
+            channel.capability_at(channel.default_value),
+            channel.capability_at(defaults[index]),
+            "{} default",
+            name
+        );
+        assert_eq!(channel.capabilities.first().unwrap().min, 0, "{}", name);
+        assert_eq!(channel.capabilities.last().unwrap().max, 255, "{}", name);
+
+        // Capability boundaries sit exactly where the typed model changes variant.
+        if typed_variant(index, 0).is_none() {
+            assert_eq!(channel.default_value, defaults[index], "{} default", name);
+            continue;
+        }
+        for value in 1..=255u8 {
+            let typed_changes = typed_variant(index, value) != typed_variant(index, value - 1);
+            let definition_changes = channel.capability_at(value) != channel.capability_at(value - 1);
+            assert_eq!(typed_changes, definition_changes, "{} at {}", name, value);
+        }
+    }
+}
+
+#[test]
+fn test_encode_named_capabilities() {
+    let mut laser = DefinedFixture::new(Arc::new(ZQ03268::definition()), "16-channel").unwrap();
+    laser.set_capability("Main switch", "On").unwrap();
+    laser.set_capability("Color mode", "rainbow").unwrap();
+    laser.set_capability_at("Rotation center", "Forward rotation", 1.0).unwrap();
+    laser.set("Pattern select", 12).unwrap();
+
+    let state = LaserState::from_channels(&laser.to_dmx_state().channels.try_into().unwrap());
+    assert_eq!(state.ch1, MainSwitch::On);
+    assert_eq!(state.ch2, ColorMode::Rainbow);
+    assert_eq!(state.ch5, 12);
+    assert_eq!(laser.get("Rotation center"), Some(191));
+    assert_eq!(laser.capability("Color mode").unwrap().name, "Rainbow");
+
+    assert!(laser.set_capability("Color mode", "Orange").is_err());
+    assert!(laser.set("Dimmer", 1).is_err());
+    assert!(DefinedFixture::new(Arc::new(ZQ03268::definition()), "8ch").is_err());
+
+    assert_eq!(Capability::new(10, 20, "Speed", "Speed").value_at(0.5), 15);
+    assert_eq!(Capability::new(20, 10, "Speed", "Backwards").value_at(1.0), 20);
+}
+
+#[test]
+fn test_parse_ofl_variants() {
+    let json = r#"{
+        "name": "Dimmer 2ch",
+        "categories": ["Dimmer"],
+        "availableChannels": {
+            "Dimmer": {
+                "defaultValue": "50%",
+                "fineChannelAliases": ["Dimmer fine"],
+                "capability": { "type": "Intensity" }
+            },
+            "Strobe": {
+                "capabilities": [
+                    { "dmxRange": [0, 9], "type": "NoFunction" },
+                    { "dmxRange": [10, 255], "type": "ShutterStrobe", "comment": "Strobe slow to fast" }
+                ]
+            }
+        },
+        "modes": [
+            { "name": "8-bit", "channels": ["Dimmer", "Strobe"] },
+            { "name": "16-bit", "channels": ["Dimmer", "Dimmer fine", "Strobe"] }
+        ]
+    }"#;
+    let definition = ofl::parse(json, "generic").unwrap();
+    assert_eq!(definition.channel("Dimmer").unwrap().default_value, 128);
+    assert_eq!(definition.channel("Dimmer").unwrap().capabilities[0].name, "Intensity");
+    assert_eq!(definition.channel("Strobe").unwrap().capabilities[1].name, "Strobe slow to fast");
+    assert_eq!(definition.mode("16-bit").unwrap().channels.len(), 3);
+
+    let bad_mode = json.replace(r#""Dimmer fine", "Strobe""#, r#""Dimmer fine", null"#);
+    assert!(ofl::parse(&bad_mode, "generic").is_err());
+    let overlap = json.replace("[10, 255]", "[9, 255]");
+    assert!(ofl::parse(&overlap, "generic").is_err());
+    let wide = json.replace("[0, 9]", "[256, 300]");
+    assert!(ofl::parse(&wide, "generic").is_err());
+    let backwards = json.replace("[0, 9]", "[9, 0]");
+    assert!(ofl::parse(&backwards, "generic").is_err());
+}
@@ -0,0 +1,102 @@
+use laserport::dmx::DmxState;
+use laserport::dmxcharts::ZQ03268::{LaserState, MainSwitch};
+use laserport::fixture::RawFixture;
+use laserport::patch::Patch;
+
+#[test]
+fn test_render_two_lasers_and_a_dimmer() {
+    let mut left = LaserState::new();
+    left.ch1 = MainSwitch::On;
+    let mut right = LaserState::new();
+    right.ch8 = 42;
+    let mut dimmer = RawFixture::new(4);
+    dimmer.values[3] = 200;
+
+    let mut patch = Patch::new();
+    patch.add("left", 1, Box::new(left)).unwrap();
+    let right_id = patch.add("right", 17, Box::new(right)).unwrap();
+    patch.add("dimmer", 509, Box::new(dimmer)).unwrap();
+
+    let universe = patch.render();
+    assert_eq!(universe.channels.len(), 512);
+    assert_eq!(universe.get_channel(1), Some(255));
+    assert_eq!(universe.get_channel(17), Some(0));
+    assert_eq!(universe.get_channel(24), Some(42));
+    assert_eq!(universe.get_channel(512), Some(200));
+
+    assert_eq!(patch.find("right"), Some(right_id));
+    assert_eq!(patch.channel_name(24).as_deref(), Some("right Pattern size"));
+    assert_eq!(patch.channel_name(510).as_deref(), Some("dimmer CH2"));
+    assert_eq!(patch.channel_name(100), None);
+}
+
+#[test]
+fn test_rejects_overlap_and_overflow() {
+    let mut patch = Patch::new();
+    patch.add("laser1", 10, Box::new(LaserState::new())).unwrap();
+
+    let err = patch.add("laser2", 25, Box::new(LaserState::new())).unwrap_err();
+    assert!(err.to_string().contains("overlaps 'laser1'"), "{}", err);
+    assert!(patch.add("laser2", 1, Box::new(LaserState::new())).is_err());
+    assert!(patch.add("laser2", 26, Box::new(LaserState::new())).is_ok());
+    assert!(patch.add("laser3", 500, Box::new(LaserState::new())).is_err());
+    assert!(patch.add("laser3", 0, Box::new(LaserState::new())).is_err());
+    assert!(patch.add("empty", 300, Box::new(RawFixture::new(0))).is_err());
+    let err = patch.add("laser1", 300, Box::new(LaserState::new())).unwrap_err();
+    assert!(err.to_string().contains("already a fixture 'laser1'"), "{}", err);
+    assert_eq!(patch.overlapping(1, 9), None);
+    assert_eq!(patch.overlapping(1, 10), Some(0));
+    assert_eq!(patch.overlapping(0, 0), None);
+    assert_eq!(patch.overlapping(usize::MAX, usize::MAX), None);
+    assert!(patch.add("huge", 1, Box::new(RawFixture::new(600))).is_err());
+}
+
+#[test]
+fn test_decode_universe() {
+    let mut patch = Patch::new();
+    patch.add("laser", 33, Box::new(LaserState::new())).unwrap();
+
+    let mut universe = DmxState::new(512);
+    universe.set_channel(33, 255);
+    universe.set_channel(40, 77);
+    patch.decode(&universe).unwrap();
+
+    let mut slots = [0u8; 16];
+    patch.get(0).unwrap().fixture().encode(&mut slots);
+    assert_eq!(slots[0], 255);
+    assert_eq!(slots[7], 77);
+    assert!(patch.decode(&DmxState::new(16)).is_err());
+}
+
+#[test]
+fn test_fixture_mut_keeps_patched_channels() {
+    let mut patch = Patch::new();
+    let id = patch.add("dimmer", 511, Box::new(RawFixture::new(2))).unwrap();
+    patch.fixture_mut(id).unwrap().decode(&[10, 20]).unwrap();
+    assert_eq!(patch.render().get_channel(512), Some(20));
+    assert_eq!(patch.get(id).unwrap().address(), 511);
+    assert!(patch.fixture_mut(id).unwrap().decode(&[1, 2, 3]).is_err());
+    assert!(patch.fixture_mut(9).is_none());
+}
+
+#[test]
+fn test_groups() {
+    let mut patch = Patch::new();
+    for (index, name) in ["laser1", "laser2", "laser3"].iter().enumerate() {
+        patch.add(name, 1 + index * 16, Box::new(LaserState::new())).unwrap();
+    }
+    patch.add("dimmer", 100, Box::new(RawFixture::new(1))).unwrap();
+
+    patch.add_group("lasers", &["laser3", "laser1", "laser2"]).unwrap();
+    assert_eq!(patch.group("lasers").unwrap().members, [2, 0, 1]);
+    assert_eq!(patch.group_channels("lasers", "Pattern size").unwrap(), [40, 8, 24]);
+
+    assert!(patch.add_group("lasers", &["laser1"]).is_err());
+    assert!(patch.add_group("empty", &[]).is_err());
+    assert!(patch.add_group("twice", &["laser1", "laser1"]).is_err());
+    assert!(patch.add_group("missing", &["laser9"]).is_err());
+    patch.add_group("all", &["laser1", "dimmer"]).unwrap();
+    let err = patch.group_channels("all", "Pattern size").unwrap_err();
+    assert!(err.to_string().contains("'dimmer' has no channel"), "{}", err);
+    assert_eq!(patch.groups().len(), 2);
+}
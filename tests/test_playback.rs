@@ -0,0 +1,138 @@
+use laserport::dmx::{DmxController, DmxState, FrameSource, DMX_FRAME_SIZE};
+use laserport::dmxcharts::ZQ03268::{ColorMode, LaserState, MainSwitch};
+use laserport::playback::{Cue, CueList, Follow, Playback};
+use laserport::transport::VirtualPort;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Renders `playback` over a blank universe at `start + at` and returns channels `1..=count`.
+fn frame_at(playback: &mut Playback, start: Instant, at: Duration, count: usize) -> Vec<u8> {
+    let mut frame = DmxState::new(DMX_FRAME_SIZE);
+    playback.render(start + at, &mut frame);
+    frame.channels[..count].to_vec()
+}
+
+#[test]
+fn test_go_fades_and_tracks() {
+    let list = CueList::new("show")
+        .with_cue(Cue::new("1").with_channel(1, 200).with_channel(2, 100).with_fade(ms(1000)))
+        .with_cue(Cue::new("2").with_channel(2, 0).with_fade_in(ms(100)).with_fade_out(ms(400)));
+    let mut playback = Playback::new(list);
+    let start = Instant::now();
+
+    assert_eq!(frame_at(&mut playback, start, ms(0), 2), [0, 0]);
+    playback.go();
+    assert_eq!(frame_at(&mut playback, start, ms(0), 2), [0, 0]);
+    assert_eq!(frame_at(&mut playback, start, ms(500), 2), [100, 50]);
+    assert!(playback.is_fading());
+    assert_eq!(frame_at(&mut playback, start, ms(1000), 2), [200, 100]);
+    assert!(!playback.is_fading());
+
+    // Cue 2 only stores CH2; CH1 tracks from cue 1. CH2 goes down, so it uses the fade-out time.
+    playback.go();
+    assert_eq!(frame_at(&mut playback, start, ms(1000), 2), [200, 100]);
+    assert_eq!(frame_at(&mut playback, start, ms(1200), 2), [200, 50]);
+    assert_eq!(frame_at(&mut playback, start, ms(1400), 2), [200, 0]);
+    assert_eq!(playback.current(), Some(1));
+
+    // GO on the last cue does nothing; BACK fades to cue 1's look with cue 1's times
+    playback.go();
+    assert_eq!(frame_at(&mut playback, start, ms(1500), 2), [200, 0]);
+    playback.back();
+    assert_eq!(frame_at(&mut playback, start, ms(1500), 2), [200, 0]);
+    assert_eq!(frame_at(&mut playback, start, ms(2000), 2), [200, 50]);
+    assert_eq!(playback.current(), Some(0));
+}
+
+#[test]
+fn test_delay_goto_and_release() {
+    let list = CueList::new("show")
+        .with_cue(Cue::new("low").with_channel(3, 40))
+        .with_cue(Cue::new("high").with_channel(3, 240).with_delay(ms(200)).with_fade(ms(200)));
+    let mut playback = Playback::new(list);
+    let start = Instant::now();
+    assert!(playback.goto(2).is_err());
+
+    playback.goto(playback.cue_list().find("high").unwrap()).unwrap();
+    assert_eq!(frame_at(&mut playback, start, ms(0), 3)[2], 0);
+    assert_eq!(frame_at(&mut playback, start, ms(150), 3)[2], 0);
+    assert_eq!(frame_at(&mut playback, start, ms(300), 3)[2], 120);
+    assert_eq!(frame_at(&mut playback, start, ms(400), 3)[2], 240);
+
+    // Channels a playback stops driving show the base state underneath again
+    let mut frame = DmxState::new(DMX_FRAME_SIZE);
+    frame.set_channel(3, 7);
+    playback.stop();
+    playback.render(start + ms(500), &mut frame);
+    assert_eq!(frame.get_channel(3), Some(7));
+    assert_eq!(playback.current(), None);
+}
+
+#[test]
+fn test_go_mid_fade_starts_from_current_output() {
+    let list = CueList::new("show")
+        .with_cue(Cue::new("1").with_channel(1, 200).with_fade(ms(1000)))
+        .with_cue(Cue::new("2").with_channel(1, 0).with_fade(ms(100)));
+    let mut playback = Playback::new(list);
+    let start = Instant::now();
+    playback.go();
+    frame_at(&mut playback, start, ms(0), 1);
+    assert_eq!(frame_at(&mut playback, start, ms(500), 1), [100]);
+    playback.go();
+    assert_eq!(frame_at(&mut playback, start, ms(550), 1), [110]);
+    assert_eq!(frame_at(&mut playback, start, ms(600), 1), [55]);
+}
+
+#[test]
+fn test_follow_and_auto_follow() {
+    let list = CueList::new("show")
+        .with_cue(Cue::new("1").with_channel(1, 10).with_follow(Follow::After(ms(300))))
+        .with_cue(Cue::new("2").with_channel(1, 110).with_fade(ms(100)).with_follow(Follow::Auto))
+        .with_cue(Cue::new("3").with_channel(1, 60).with_fade(ms(100)));
+    let mut playback = Playback::new(list);
+    let start = Instant::now();
+    playback.go();
+    assert_eq!(frame_at(&mut playback, start, ms(0), 1), [10]);
+    assert_eq!(frame_at(&mut playback, start, ms(250), 1), [10]);
+    assert_eq!(playback.current(), Some(0));
+
+    // Cue 2 started at 300ms even though no frame was rendered then; it ends at 400ms
+    // and cue 3 auto-follows from there.
+    assert_eq!(frame_at(&mut playback, start, ms(350), 1), [60]);
+    assert_eq!(frame_at(&mut playback, start, ms(450), 1), [85]);
+    assert_eq!(playback.current(), Some(2));
+    assert_eq!(frame_at(&mut playback, start, ms(2000), 1), [60]);
+}
+
+#[test]
+fn test_playback_in_refresh_loop() {
+    let mut on = LaserState::new();
+    on.ch1 = MainSwitch::On;
+    on.ch2 = ColorMode::Rainbow;
+    let list = CueList::new("laser").with_cue(Cue::new("on").with_fixture(17, &on).unwrap().with_fade(ms(200)));
+    let playback = Playback::new(list);
+
+    let port = VirtualPort::new("virtual0");
+    let recording = port.recording();
+    let controller = DmxController::with_transport(Box::new(port.open_dmx()), 1).unwrap();
+    let mut universe = DmxState::new(DMX_FRAME_SIZE);
+    universe.set_channel(1, 99);
+    let refresh = controller.start_refresh(universe, 40.0).unwrap();
+    refresh.handle().add_source(Box::new(playback.clone()));
+    thread::sleep(ms(100));
+    playback.go();
+    thread::sleep(ms(400));
+    refresh.stop().unwrap();
+
+    let frames = recording.frames();
+    let switch: Vec<u8> = frames.iter().map(|frame| frame.channel(17).unwrap()).collect();
+    assert_eq!(switch.first(), Some(&0));
+    assert_eq!(switch.last(), Some(&on.ch1.to_u8()));
+    assert!(switch.iter().any(|&value| value > 0 && value < on.ch1.to_u8()), "no fade in {:?}", switch);
+    assert!(frames.iter().all(|frame| frame.channel(1) == Some(99)));
+    assert_eq!(frames.last().unwrap().channel(18), Some(ColorMode::Rainbow.to_u8()));
+}
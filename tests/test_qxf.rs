@@ -0,0 +1,139 @@
+use laserport::dmx::DmxController;
+use laserport::dmxcharts::ZQ03268::{self, ColorMode, LaserState, MainSwitch};
+use laserport::fixture::{ofl, qxf, DefinedFixture};
+use laserport::transport::VirtualPort;
+use std::sync::Arc;
+
+#[test]
+fn test_qlc_profile_matches_bundled_definition() {
+    let qlc = qxf::load("tests/data/UKing-ZQ03268.qxf").unwrap();
+    let ofl = ZQ03268::definition();
+    assert_eq!(qlc.manufacturer, "U'King");
+    assert_eq!(qlc.name, ofl.name);
+    assert_eq!(qlc.categories, ["Laser"]);
+    assert_eq!(qlc.physical, ofl.physical);
+    assert_eq!(qlc.modes[0].channels, ofl.modes[0].channels);
+
+    for (qlc_channel, ofl_channel) in qlc.channels.iter().zip(&ofl.channels) {
+        assert_eq!(qlc_channel.name, ofl_channel.name);
+        assert_eq!(qlc_channel.default_value, ofl_channel.default_value, "{}", qlc_channel.name);
+        let ranges = |channel: &laserport::fixture::ChannelDefinition| {
+            channel.capabilities.iter().map(|cap| (cap.min, cap.max, cap.name.clone())).collect::<Vec<_>>()
+        };
+        assert_eq!(ranges(qlc_channel), ranges(ofl_channel));
+    }
+    assert_eq!(qlc.channel("Color mode").unwrap().capabilities[0].kind, "ColorPreset");
+    assert_eq!(qlc.channel("Horizontal movement").unwrap().capabilities[0].kind, "Pan");
+}
+
+#[test]
+fn test_drive_qlc_profile_through_controller() {
+    let definition = Arc::new(qxf::load("tests/data/UKing-ZQ03268.qxf").unwrap());
+    let mut laser = DefinedFixture::new(definition, "16-channel").unwrap();
+    laser.set_capability("Main switch", "On").unwrap();
+    laser.set_capability("Color mode", "Cyan").unwrap();
+
+    let port = VirtualPort::new("virtual0");
+    let recording = port.recording();
+    let mut controller = DmxController::with_transport(Box::new(port.open_dmx()), 17).unwrap();
+    controller.send(&laser.to_dmx_state()).unwrap();
+
+    let frame = recording.frame(0);
+    let state = LaserState::from_channels(&frame.slots()[16..32].try_into().unwrap());
+    assert_eq!(state.ch1, MainSwitch::On);
+    assert_eq!(state.ch2, ColorMode::FixedCyan);
+}
+
+#[test]
+fn test_parse_qxf_variants() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE FixtureDefinition>
+<FixtureDefinition xmlns="http://www.qlcplus.org/FixtureDefinition">
+ <Manufacturer>Generic</Manufacturer>
+ <Model>Dimmer &amp; Strobe</Model>
+ <Type>Dimmer</Type>
+ <Channel Name="Dimmer" Preset="IntensityDimmer" Default="128"/>
+ <Channel Name="Strobe">
+  <Group Byte="0">Shutter</Group>
+  <Capability Min="0" Max="9" Preset="ShutterOpen">Open</Capability>
+  <Capability Min="10" Max="255" Preset="StrobeSlowToFast">Strobe slow to fast</Capability>
+ </Channel>
+ <Mode Name="2 Channel">
+  <Channel Number="1">Strobe</Channel>
+  <Channel Number="0">Dimmer</Channel>
+ </Mode>
+ <Physical>
+  <Bulb Type="LED" Lumens="0" ColourTemperature="0"/>
+  <Dimensions Weight="2.5" Width="300" Height="120" Depth="80"/>
+  <Technical PowerConsumption="45" DmxConnector="3-pin and 5-pin"/>
+ </Physical>
+</FixtureDefinition>"#;
+    let definition = qxf::parse(xml).unwrap();
+    assert_eq!(definition.name, "Dimmer & Strobe");
+    assert_eq!(definition.modes[0].channels, ["Dimmer", "Strobe"]);
+
+    let dimmer = definition.channel("Dimmer").unwrap();
+    assert_eq!(dimmer.default_value, 128);
+    assert_eq!((dimmer.capabilities[0].min, dimmer.capabilities[0].max), (0, 255));
+    assert_eq!(dimmer.capabilities[0].kind, "Intensity");
+    assert_eq!(definition.channel("Strobe").unwrap().capability_at(200).unwrap().name, "Strobe slow to fast");
+
+    assert_eq!(definition.physical.dimensions_mm, Some([300.0, 120.0, 80.0]));
+    assert_eq!(definition.physical.weight_kg, Some(2.5));
+    assert_eq!(definition.physical.power_w, Some(45.0));
+    assert_eq!(definition.physical.bulb.as_deref(), Some("LED"));
+
+    let gap = xml.replace(r#"Number="1""#, r#"Number="2""#);
+    assert!(qxf::parse(&gap).is_err());
+    let overlap = xml.replace(r#"Min="10""#, r#"Min="9""#);
+    assert!(qxf::parse(&overlap).is_err());
+    let wide = xml.replace(r#"Min="0" Max="9""#, r#"Min="256" Max="300""#);
+    assert!(qxf::parse(&wide).is_err());
+    let backwards = xml.replace(r#"Min="0" Max="9""#, r#"Min="9" Max="0""#);
+    assert!(qxf::parse(&backwards).is_err());
+    let undefined = xml.replace(">Strobe</Channel>", ">Pan</Channel>");
+    assert!(qxf::parse(&undefined).is_err());
+}
+
+#[test]
+fn test_fine_channels_survive_round_trip() {
+    let json = r#"{
+        "name": "Scanner 16-bit",
+        "categories": ["Scanner"],
+        "availableChannels": {
+            "Pan": { "fineChannelAliases": ["Pan fine"], "capability": { "type": "Pan", "comment": "Pan" } },
+            "Tilt": { "fineChannelAliases": ["Tilt fine"], "capability": { "type": "Tilt", "comment": "Tilt" } },
+            "Dimmer": { "capability": { "type": "Intensity" } }
+        },
+        "modes": [{ "name": "5-channel", "channels": ["Pan", "Pan fine", "Tilt", "Tilt fine", "Dimmer"] }]
+    }"#;
+    let definition = ofl::parse(json, "Generic").unwrap();
+
+    let exported = qxf::export(&definition);
+    assert!(exported.contains(r#"<Channel Name="Pan fine" Default="0">
+  <Group Byte="1">Pan</Group>"#), "{}", exported);
+    let imported = qxf::parse(&exported).unwrap();
+    assert_eq!(imported.channels, definition.channels);
+    assert_eq!(imported.channel("Tilt").unwrap().fine_channel_aliases, ["Tilt fine"]);
+
+    // QLC+ 4.12 files mark fine channels with a preset instead of a byte
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<FixtureDefinition xmlns="http://www.qlcplus.org/FixtureDefinition">
+ <Manufacturer>Generic</Manufacturer>
+ <Model>Dimmer 16-bit</Model>
+ <Type>Dimmer</Type>
+ <Channel Name="Dimmer fine" Preset="IntensityDimmerFine"/>
+ <Channel Name="Dimmer" Preset="IntensityDimmer"/>
+ <Mode Name="16-bit">
+  <Channel Number="0">Dimmer</Channel>
+  <Channel Number="1">Dimmer fine</Channel>
+ </Mode>
+</FixtureDefinition>"#;
+    let dimmer = qxf::parse(xml).unwrap();
+    assert_eq!(dimmer.channel("Dimmer").unwrap().fine_channel_aliases, ["Dimmer fine"]);
+    assert_eq!(dimmer.channel("Dimmer fine").unwrap().capabilities[0].kind, "Generic");
+
+    // A fine channel with no coarse channel of its group is an error, not a plain channel
+    let orphan = xml.replace(r#"Preset="IntensityDimmer"/>"#, r#"Preset="ShutterStrobe"/>"#);
+    assert!(qxf::parse(&orphan).is_err());
+}
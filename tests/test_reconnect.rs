@@ -0,0 +1,146 @@
+use laserport::dmx::{DmxController, DmxState, DmxTiming, DMX_FRAME_SIZE};
+use laserport::transport::discovery::DiscoveredPort;
+use laserport::transport::{AdapterAliases, DmxTransport, PortSelector, ReconnectingTransport, TransportCapabilities};
+use serialport::{SerialPortInfo, SerialPortType, UsbPortInfo};
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// An adapter that can be unplugged: sends fail and it can't be reopened while out.
+#[derive(Clone, Default)]
+struct Cable {
+    unplugged: Arc<AtomicBool>,
+    frames: Arc<Mutex<Vec<Vec<u8>>>>,
+    timings_applied: Arc<AtomicUsize>,
+}
+
+impl DmxTransport for Cable {
+    fn open(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn send_universe(&mut self, slots: &[u8]) -> Result<(), Box<dyn Error>> {
+        if self.unplugged.load(Ordering::SeqCst) {
+            return Err("device not configured".into());
+        }
+        self.frames.lock().unwrap().push(slots.to_vec());
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn capabilities(&self) -> TransportCapabilities {
+        TransportCapabilities { max_slots: DMX_FRAME_SIZE, generates_break: true, networked: false }
+    }
+
+    fn set_timing(&mut self, _timing: &DmxTiming) -> Result<(), Box<dyn Error>> {
+        self.timings_applied.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+fn usb_port(name: &str, serial: &str) -> DiscoveredPort {
+    DiscoveredPort::from_info(&SerialPortInfo {
+        port_name: name.to_string(),
+        port_type: SerialPortType::UsbPort(UsbPortInfo {
+            vid: 0x0403,
+            pid: 0x6001,
+            serial_number: Some(serial.to_string()),
+            manufacturer: Some("FTDI".to_string()),
+            product: Some("FT232R USB UART".to_string()),
+        }),
+    })
+}
+
+#[test]
+fn test_parse_selectors() {
+    assert_eq!("COM4".parse::<PortSelector>().unwrap(), PortSelector::Name("COM4".to_string()));
+    assert_eq!(
+        "serial:EN123456".parse::<PortSelector>().unwrap(),
+        PortSelector::SerialNumber("EN123456".to_string())
+    );
+    assert_eq!("alias:left".parse::<PortSelector>().unwrap(), PortSelector::Alias("left".to_string()));
+    assert_eq!(PortSelector::SerialNumber("A1".to_string()).to_string(), "serial:A1");
+    assert!("serial:".parse::<PortSelector>().is_err());
+}
+
+#[test]
+fn test_find_by_serial_and_alias() {
+    let ports = vec![usb_port("/dev/ttyUSB0", "A10KXYZ"), usb_port("/dev/ttyUSB1", "EN123456")];
+    let mut aliases = AdapterAliases::new();
+    aliases.insert("stage-left", &"serial:EN123456".parse().unwrap()).unwrap();
+    assert!(aliases.insert("loop", &PortSelector::Alias("stage-left".to_string())).is_err());
+
+    let by_serial: PortSelector = "serial:en123456".parse().unwrap();
+    assert_eq!(by_serial.find(&ports, &aliases).unwrap().unwrap().port_name, "/dev/ttyUSB1");
+    let by_alias: PortSelector = "alias:stage-left".parse().unwrap();
+    assert_eq!(by_alias.find(&ports, &aliases).unwrap().unwrap().port_name, "/dev/ttyUSB1");
+
+    let missing: PortSelector = "serial:NOPE".parse().unwrap();
+    assert!(missing.find(&ports, &aliases).unwrap().is_none());
+    let unknown: PortSelector = "alias:stage-right".parse().unwrap();
+    assert!(unknown.find(&ports, &aliases).is_err());
+}
+
+#[test]
+fn test_alias_file_round_trip() {
+    let path = std::env::temp_dir().join(format!("laserport-aliases-{}", std::process::id())).join("adapters.json");
+    let mut aliases = AdapterAliases::new();
+    aliases.insert("stage-left", &"serial:EN123456".parse().unwrap()).unwrap();
+    aliases.insert("bench", &"/dev/ttyUSB0".parse().unwrap()).unwrap();
+    aliases.save(&path).unwrap();
+
+    let loaded = AdapterAliases::load(&path).unwrap();
+    assert_eq!(loaded, aliases);
+    assert_eq!(loaded.get("bench").unwrap(), PortSelector::Name("/dev/ttyUSB0".to_string()));
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_reconnects_and_resumes_universe() {
+    let cable = Cable::default();
+    let connects = Arc::new(AtomicUsize::new(0));
+    let connector_cable = cable.clone();
+    let connector_count = connects.clone();
+    let transport = ReconnectingTransport::new(Box::new(move || {
+        if connector_cable.unplugged.load(Ordering::SeqCst) {
+            return Err("no such device".into());
+        }
+        connector_count.fetch_add(1, Ordering::SeqCst);
+        Ok(Box::new(connector_cable.clone()) as Box<dyn DmxTransport>)
+    }))
+    .with_retry_interval(Duration::from_millis(20));
+
+    let mut controller = DmxController::with_transport(Box::new(transport), 1).unwrap();
+    controller.set_timing(DmxTiming { slots: 16, ..DmxTiming::default() }).unwrap();
+    let refresh = controller.start_refresh(DmxState::new(1), 40.0).unwrap();
+    let dmx = refresh.handle();
+    dmx.set_channel(1, 10);
+    std::thread::sleep(Duration::from_millis(100));
+
+    cable.unplugged.store(true, Ordering::SeqCst);
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(refresh.last_error().unwrap().contains("disconnected"));
+    dmx.set_channel(1, 200);
+    let frames_while_out = cable.frames.lock().unwrap().len();
+
+    cable.unplugged.store(false, Ordering::SeqCst);
+    std::thread::sleep(Duration::from_millis(150));
+    drop(refresh.stop().unwrap());
+
+    let frames = cable.frames.lock().unwrap();
+    assert!(frames.len() > frames_while_out);
+    assert_eq!(frames.last().unwrap()[0], 200);
+    assert_eq!(frames.last().unwrap().len(), 16);
+    assert_eq!(connects.load(Ordering::SeqCst), 2);
+    // Timing set once by the caller, then reapplied on reconnect.
+    assert_eq!(cable.timings_applied.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_connect_fails_when_adapter_is_missing() {
+    assert!(DmxController::connect("serial:LASERPORT-NONE", 1).is_err());
+}
@@ -0,0 +1,76 @@
+#![cfg(unix)]
+
+use laserport::dmxcharts::ZQ03268::{ColorMode, GraphicsGroup, LaserState, MainSwitch};
+use laserport::transport::loopback::PtyLoopback;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// Runs `laserport shell`, feeding it `lines` one at a time with `pause` between them.
+fn shell(loopback: &PtyLoopback, args: &[&str], lines: &[&str], pause: Duration) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_laserport"))
+        .args(["--port", loopback.port_name(), "--adapter", "open-dmx", "shell"])
+        .args(args)
+        .env("LASERPORT_ADAPTERS", "/nonexistent/adapters.json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    for line in lines {
+        writeln!(stdin, "{}", line).unwrap();
+        stdin.flush().unwrap();
+        thread::sleep(pause);
+    }
+    drop(stdin);
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_shell_sets_channels_by_number_and_name() {
+    let loopback = PtyLoopback::open_dmx().unwrap();
+    let lines = ["switch on", "ch7 200", "color seg8", "group anim3", "pattern 9", "dmx 100 42", "bogus 1", "show", "quit"];
+    let output = shell(&loopback, &["--address", "17"], &lines, Duration::from_millis(50));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("error: unknown channel or command 'bogus'"), "{}", stdout);
+    assert!(stdout.contains("Seg8"), "{}", stdout);
+
+    let frames = loopback.drain_frames();
+    let last = frames.last().expect("no frames sent");
+    let state = LaserState::from_channels(&last[17..33].try_into().unwrap());
+    assert_eq!(state.ch1, MainSwitch::On);
+    assert_eq!(state.ch7, 200);
+    assert_eq!(state.ch2, ColorMode::Seg8);
+    assert_eq!(state.ch4, GraphicsGroup::Animation3);
+    assert_eq!(state.ch5, 9);
+    assert_eq!(last[100], 42);
+}
+
+#[test]
+fn test_shell_fades_in_the_background() {
+    let loopback = PtyLoopback::open_dmx().unwrap();
+    let lines = ["fade ch8 0..250 400ms", "quit"];
+    let output = shell(&loopback, &[], &lines, Duration::from_millis(600));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // Size starts at 128 until the fade jumps it to 0
+    let sizes: Vec<u8> = loopback.drain_frames().iter().map(|frame| frame[8]).skip_while(|&size| size != 0).collect();
+    assert_eq!(sizes.last(), Some(&250));
+    assert!(sizes.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", sizes);
+    let steps = sizes.iter().filter(|&&size| size > 0 && size < 250).count();
+    assert!(steps >= 5, "only {} intermediate values in {:?}", steps, sizes);
+}
+
+#[test]
+fn test_newer_command_cancels_fade() {
+    let loopback = PtyLoopback::open_dmx().unwrap();
+    let lines = ["fade size 0..255 2s", "size 10", "quit"];
+    let output = shell(&loopback, &[], &lines, Duration::from_millis(300));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let frames = loopback.drain_frames();
+    assert_eq!(frames.last().map(|frame| frame[8]), Some(10));
+}
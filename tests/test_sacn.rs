@@ -0,0 +1,63 @@
+use laserport::dmx::{DmxController, DmxState};
+use laserport::transport::sacn::{self, OPTION_STREAM_TERMINATED};
+use laserport::transport::SacnTransport;
+use std::net::{Ipv4Addr, UdpSocket};
+use std::time::Duration;
+
+#[test]
+fn test_multicast_address() {
+    assert_eq!(sacn::multicast_address(1), Ipv4Addr::new(239, 255, 0, 1));
+    assert_eq!(sacn::multicast_address(0x1234), Ipv4Addr::new(239, 255, 0x12, 0x34));
+}
+
+#[test]
+fn test_rejects_invalid_settings() {
+    assert!(SacnTransport::multicast(0, "laserport").is_err());
+    assert!(SacnTransport::multicast(64000, "laserport").is_err());
+    assert!(SacnTransport::multicast(1, "laserport").unwrap().with_priority(201).is_err());
+}
+
+#[test]
+fn test_sacn_over_udp() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+
+    let cid = [7u8; 16];
+    let transport = SacnTransport::unicast(receiver.local_addr().unwrap(), 5, "laserport test")
+        .unwrap()
+        .with_cid(cid)
+        .with_priority(150)
+        .unwrap();
+    let mut controller = DmxController::with_transport(Box::new(transport), 1).unwrap();
+
+    let mut state = DmxState::new(16);
+    state.set_channel(1, 255);
+    state.set_channel(16, 42);
+    controller.send(&state).unwrap();
+
+    let mut buf = [0u8; 1024];
+    let len = receiver.recv(&mut buf).unwrap();
+    assert_eq!(len, 638);
+    assert_eq!(&buf[16..18], &[0x72, 0x6E]); // Root PDU length 622
+    assert_eq!(&buf[38..40], &[0x72, 0x58]); // Framing PDU length 600
+    assert_eq!(&buf[115..117], &[0x72, 0x0B]); // DMP PDU length 523
+
+    let (header, slots) = sacn::decode_data_packet(&buf[..len]).unwrap();
+    assert_eq!(header.cid, cid);
+    assert_eq!(header.source_name, "laserport test");
+    assert_eq!(header.priority, 150);
+    assert_eq!(header.sequence, 1);
+    assert_eq!(header.options, 0);
+    assert_eq!(header.universe, 5);
+    assert_eq!(slots.len(), 512);
+    assert_eq!(slots[0], 255);
+    assert_eq!(slots[15], 42);
+
+    controller.close().unwrap();
+    for expected_sequence in 2..=4u8 {
+        let len = receiver.recv(&mut buf).unwrap();
+        let (header, _) = sacn::decode_data_packet(&buf[..len]).unwrap();
+        assert_eq!(header.sequence, expected_sequence);
+        assert_eq!(header.options & OPTION_STREAM_TERMINATED, OPTION_STREAM_TERMINATED);
+    }
+}
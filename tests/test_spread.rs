@@ -0,0 +1,75 @@
+use laserport::dmx::{DmxState, FrameSource, DMX_FRAME_SIZE};
+use laserport::dmxcharts::ZQ03268::LaserState;
+use laserport::patch::Patch;
+use laserport::playback::{Effects, Lfo, Spread, Waveform};
+use std::time::{Duration, Instant};
+
+fn phases(spread: &Spread, members: usize) -> Vec<f32> {
+    spread.offsets(members).iter().map(|member| member.phase).collect()
+}
+
+#[test]
+fn test_phase_patterns() {
+    assert_eq!(phases(&Spread::wave(1.0), 4), [0.0, 0.25, 0.5, 0.75]);
+    assert_eq!(phases(&Spread::wave(0.5), 4), [0.0, 0.125, 0.25, 0.375]);
+    assert_eq!(phases(&Spread::mirror(1.0), 4), [0.5, 0.0, 0.0, 0.5]);
+    assert_eq!(phases(&Spread::mirror(1.0), 5), [2.0 / 3.0, 1.0 / 3.0, 0.0, 1.0 / 3.0, 2.0 / 3.0]);
+    assert_eq!(phases(&Spread::wave(1.0), 1), [0.0]);
+
+    // Random is a fixed shuffle of the wave's phases
+    let random = phases(&Spread::random(1.0, 42), 6);
+    assert_eq!(random, phases(&Spread::random(1.0, 42), 6));
+    let mut sorted = random.clone();
+    sorted.sort_by(f32::total_cmp);
+    assert_eq!(sorted, phases(&Spread::wave(1.0), 6));
+}
+
+#[test]
+fn test_grouping_wings_and_fan() {
+    assert_eq!(phases(&Spread::wave(1.0).with_grouping(2), 4), [0.0, 0.0, 0.5, 0.5]);
+    // Two wings of two: the second wing runs the pattern mirrored
+    assert_eq!(phases(&Spread::wave(1.0).with_wings(2), 4), [0.0, 0.5, 0.5, 0.0]);
+    assert_eq!(phases(&Spread::wave(1.0).with_wings(2), 5), [0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0 / 3.0, 0.0]);
+
+    let fan: Vec<f32> = Spread::fan(30.0).offsets(4).iter().map(|member| member.offset.round()).collect();
+    assert_eq!(fan, [-30.0, -10.0, 10.0, 30.0]);
+    assert_eq!(phases(&Spread::fan(30.0), 4), [0.0; 4]);
+}
+
+#[test]
+fn test_wave_across_four_lasers() {
+    let mut patch = Patch::new();
+    let names = ["laser1", "laser2", "laser3", "laser4"];
+    for (index, name) in names.iter().enumerate() {
+        patch.add(name, 1 + index * 16, Box::new(LaserState::new())).unwrap();
+    }
+    patch.add_group("row", &names).unwrap();
+
+    let mut effects = Effects::new();
+    let sweep = Lfo::between(Waveform::Square, 1.0, 0, 100);
+    let ids = effects.add_to_group(&patch, "row", "Horizontal movement", sweep, &Spread::wave(1.0)).unwrap();
+    assert_eq!(ids.len(), 4);
+    let pulse = Lfo::new(Waveform::Sine, 1.0, 1.0);
+    assert!(effects.add_to_group(&patch, "nobody", "Horizontal movement", pulse, &Spread::wave(1.0)).is_err());
+
+    let start = Instant::now();
+    let positions = |ms: u64, effects: &mut Effects| {
+        let mut frame = DmxState::new(DMX_FRAME_SIZE);
+        effects.render(start + Duration::from_millis(ms), &mut frame);
+        let channels = patch.group_channels("row", "Horizontal movement").unwrap();
+        channels.iter().map(|&ch| frame.channels[ch - 1]).collect::<Vec<u8>>()
+    };
+    // A square wave a quarter cycle apart per laser: the "on" half rolls along the row
+    assert_eq!(positions(0, &mut effects), [100, 100, 0, 0]);
+    assert_eq!(positions(300, &mut effects), [100, 0, 0, 100]);
+    assert_eq!(positions(550, &mut effects), [0, 0, 100, 100]);
+
+    // Fanned offsets keep the same clock for every member
+    let mut effects = Effects::new();
+    let channels = patch.group_channels("row", "Pattern size").unwrap();
+    effects.add_spread(&channels, Lfo::new(Waveform::Sine, 1.0, 0.0).with_offset(100.0), &Spread::fan(30.0));
+    let mut frame = DmxState::new(DMX_FRAME_SIZE);
+    effects.render(start, &mut frame);
+    let sizes: Vec<u8> = channels.iter().map(|&ch| frame.channels[ch - 1]).collect();
+    assert_eq!(sizes, [70, 90, 110, 130]);
+}
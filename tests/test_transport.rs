@@ -0,0 +1,128 @@
+use laserport::dmx::{DmxController, DmxState, DmxTiming, DMX_FRAME_SIZE};
+use laserport::transport::{DmxTransport, TransportCapabilities};
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+struct RecordingTransport {
+    frames: Arc<Mutex<Vec<Vec<u8>>>>,
+    open: bool,
+}
+
+impl DmxTransport for RecordingTransport {
+    fn open(&mut self) -> Result<(), Box<dyn Error>> {
+        self.open = true;
+        Ok(())
+    }
+
+    fn send_universe(&mut self, slots: &[u8]) -> Result<(), Box<dyn Error>> {
+        if !self.open {
+            return Err("not open".into());
+        }
+        self.frames.lock().unwrap().push(slots.to_vec());
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        self.open = false;
+        Ok(())
+    }
+
+    fn capabilities(&self) -> TransportCapabilities {
+        TransportCapabilities { max_slots: DMX_FRAME_SIZE, generates_break: false, networked: false }
+    }
+}
+
+#[test]
+fn test_controller_places_state_at_address() {
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    let transport = RecordingTransport { frames: frames.clone(), open: false };
+    let mut controller = DmxController::with_transport(Box::new(transport), 10).unwrap();
+
+    let mut state = DmxState::new(3);
+    state.set_channel(1, 255);
+    state.set_channel(3, 70);
+    controller.send(&state).unwrap();
+
+    let frames = frames.lock().unwrap();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].len(), DMX_FRAME_SIZE);
+    assert_eq!(frames[0][9], 255);
+    assert_eq!(frames[0][10], 0);
+    assert_eq!(frames[0][11], 70);
+}
+
+#[test]
+fn test_controller_rejects_invalid_address() {
+    let transport = RecordingTransport { frames: Arc::default(), open: false };
+    assert!(DmxController::with_transport(Box::new(transport), 0).is_err());
+}
+
+#[test]
+fn test_refresh_retransmits_latest_state() {
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    let transport = RecordingTransport { frames: frames.clone(), open: false };
+    let controller = DmxController::with_transport(Box::new(transport), 1).unwrap();
+
+    let refresh = controller.start_refresh(DmxState::new(16), 40.0).unwrap();
+    let handle = refresh.handle();
+    std::thread::sleep(Duration::from_millis(150));
+    handle.set_channel(7, 200);
+    std::thread::sleep(Duration::from_millis(150));
+    let controller = refresh.stop().unwrap();
+    drop(controller);
+    // Read once the thread has joined, so no frame is sent after the count
+    let sent = handle.frames_sent();
+
+    let frames = frames.lock().unwrap();
+    assert_eq!(frames.len() as u64, sent);
+    // ~12 frames at 40 Hz over 300ms; allow for a slow CI scheduler.
+    assert!(frames.len() >= 6, "only {} frames sent", frames.len());
+    assert_eq!(frames[0][6], 0);
+    assert_eq!(frames.last().unwrap()[6], 200);
+}
+
+#[test]
+fn test_refresh_rejects_invalid_rate() {
+    let transport = RecordingTransport { frames: Arc::default(), open: false };
+    let controller = DmxController::with_transport(Box::new(transport), 1).unwrap();
+    assert!(controller.start_refresh(DmxState::new(16), 0.0).is_err());
+}
+
+#[test]
+fn test_timing_limits() {
+    let timing = DmxTiming::default();
+    assert!(timing.validate().is_ok());
+    assert!((timing.max_refresh_rate() - 44.0).abs() < 0.5);
+
+    assert!(DmxTiming { break_time: Duration::from_micros(88), ..timing }.validate().is_err());
+    assert!(DmxTiming { mark_after_break: Duration::from_micros(8), ..timing }.validate().is_err());
+    assert!(DmxTiming { inter_frame_gap: Duration::from_secs(1), ..timing }.validate().is_err());
+    assert!(DmxTiming { slots: 0, ..timing }.validate().is_err());
+    assert!(DmxTiming { slots: 513, ..timing }.validate().is_err());
+
+    // Short frames are padded to the 1204µs break-to-break minimum.
+    let short = DmxTiming { slots: 16, ..timing };
+    assert_eq!(short.frame_period(), Duration::from_micros(1204));
+}
+
+#[test]
+fn test_short_frame() {
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    let transport = RecordingTransport { frames: frames.clone(), open: false };
+    let mut controller = DmxController::with_transport(Box::new(transport), 1).unwrap();
+    controller.set_timing(DmxTiming { slots: 16, ..DmxTiming::default() }).unwrap();
+    assert!(controller.set_timing(DmxTiming { slots: 0, ..DmxTiming::default() }).is_err());
+    assert_eq!(controller.timing().slots, 16);
+
+    let mut state = DmxState::new(20);
+    state.set_channel(16, 9);
+    state.set_channel(17, 9);
+    controller.send(&state).unwrap();
+    assert_eq!(frames.lock().unwrap()[0].len(), 16);
+    assert_eq!(frames.lock().unwrap()[0][15], 9);
+
+    // A 16-slot frame allows far more than the 44 Hz of a full universe.
+    let refresh = controller.start_refresh(state, 100.0).unwrap();
+    refresh.stop().unwrap();
+}
@@ -0,0 +1,93 @@
+use laserport::dmx::{DmxController, DmxState, DmxTiming};
+use laserport::transport::enttec::{self, LABEL_GET_SERIAL_NUMBER, LABEL_SEND_DMX_PACKET};
+use laserport::transport::mock::PortEvent;
+use laserport::transport::VirtualPort;
+use std::time::Duration;
+
+#[test]
+fn test_open_dmx_frames_are_recorded() {
+    let port = VirtualPort::new("virtual0");
+    let recording = port.recording();
+    let mut controller = DmxController::with_transport(Box::new(port.open_dmx()), 5).unwrap();
+
+    let mut state = DmxState::new(2);
+    state.set_channel(1, 255);
+    state.set_channel(2, 40);
+    controller.send(&state).unwrap();
+    state.set_channel(2, 41);
+    controller.send(&state).unwrap();
+
+    assert_eq!(recording.frame_count(), 2);
+    let frame = recording.frame(0);
+    assert_eq!(frame.start_code(), Some(0));
+    assert_eq!(frame.slots().len(), 512);
+    recording.assert_channel(0, 5, 255);
+    recording.assert_channel(0, 6, 40);
+    recording.assert_channel(1, 6, 41);
+    recording.assert_timing(&DmxTiming::default());
+
+    let events = recording.events();
+    assert_eq!(events[0].1, PortEvent::SetBreak);
+    assert_eq!(events[1].1, PortEvent::ClearBreak);
+}
+
+#[test]
+fn test_custom_timing_and_refresh_gaps() {
+    let port = VirtualPort::new("virtual0");
+    let recording = port.recording();
+    let mut controller = DmxController::with_transport(Box::new(port.open_dmx()), 1).unwrap();
+    let timing = DmxTiming {
+        break_time: Duration::from_micros(200),
+        mark_after_break: Duration::from_micros(40),
+        inter_frame_gap: Duration::from_millis(5),
+        slots: 24,
+    };
+    controller.set_timing(timing).unwrap();
+
+    let refresh = controller.start_refresh(DmxState::new(1), 40.0).unwrap();
+    refresh.handle().set_channel(1, 128);
+    std::thread::sleep(Duration::from_millis(150));
+    drop(refresh.stop().unwrap());
+
+    assert!(recording.frame_count() >= 3);
+    assert_eq!(recording.frame(0).slots().len(), 24);
+    assert_eq!(recording.last_frame().unwrap().channel(1), Some(128));
+    recording.assert_timing(&timing);
+    // 40 Hz is a 25ms period; single gaps jitter with the scheduler, so check the mean
+    let frames = recording.frames();
+    let span = frames.last().unwrap().at - frames[0].at;
+    let mean = span / (frames.len() as u32 - 1);
+    assert!(mean >= Duration::from_millis(23) && mean <= Duration::from_millis(35), "mean period {:?}", mean);
+}
+
+#[test]
+fn test_enttec_pro_over_virtual_port() {
+    let port = VirtualPort::new("virtual1");
+    let recording = port.recording();
+    port.push_input(&enttec::encode_message(LABEL_GET_SERIAL_NUMBER, &[0x78, 0x56, 0x34, 0x12]));
+
+    let mut widget = port.enttec_pro();
+    assert_eq!(widget.serial_number().unwrap(), "12345678");
+    recording.clear();
+
+    let mut controller = DmxController::with_transport(Box::new(widget), 1).unwrap();
+    let mut state = DmxState::new(1);
+    state.set_channel(1, 99);
+    controller.send(&state).unwrap();
+
+    let frame = recording.frame(0);
+    assert_eq!(frame.break_time, None);
+    let (label, payload) = enttec::read_message(&mut frame.bytes.as_slice()).unwrap();
+    assert_eq!(label, LABEL_SEND_DMX_PACKET);
+    assert_eq!(payload[1], 99);
+}
+
+#[test]
+#[should_panic(expected = "channel 1 in frame 0")]
+fn test_assert_channel_reports_mismatch() {
+    let port = VirtualPort::new("virtual0");
+    let recording = port.recording();
+    let mut controller = DmxController::with_transport(Box::new(port.open_dmx()), 1).unwrap();
+    controller.send(&DmxState::new(1)).unwrap();
+    recording.assert_channel(0, 1, 255);
+}